@@ -1,5 +1,12 @@
+use std::{net::SocketAddr, time::Duration};
+
 use clap::{arg, command, Parser, Subcommand};
 
+use crate::utils::{
+    parse_end_of_day, parse_interval, parse_start_of_day, ChallengeCheck, DeviceId, PoolSort,
+    RoundKind, RoundStatus,
+};
+
 #[derive(Parser, Debug)]
 pub struct AccountArgs {
     #[arg(value_name = "ADDRESS", help = "The address to the account to fetch.")]
@@ -60,11 +67,23 @@ pub struct BenchmarkArgs {
     #[arg(
         long,
         short,
-        value_name = "THREAD_COUNT",
-        help = "The number of cores to use during the benchmark",
+        value_name = "CORES",
+        help = "The number of cores to use during the benchmark, or a list of core ids such as 0-7,16-23.",
         default_value = "1"
     )]
     pub cores: String,
+
+    #[arg(
+        long,
+        help = "Skip SMT siblings so only one thread runs per physical core."
+    )]
+    pub skip_smt: bool,
+
+    #[arg(
+        long,
+        help = "Do not pin threads to cores. Allows a thread count above the number of cores."
+    )]
+    pub no_pin: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -117,6 +136,98 @@ pub struct ClaimArgs {
     pub pool_url: Option<String>,
 }
 
+#[derive(Parser, Debug)]
+pub struct EstimateArgs {
+    #[arg(
+        long,
+        value_name = "HASHES_PER_SEC",
+        help = "Hashrate of the machine. Measured with a short benchmark when omitted."
+    )]
+    pub hashrate: Option<u64>,
+
+    #[arg(
+        long,
+        short,
+        value_name = "CORES",
+        help = "The number of cores to measure the hashrate with, or a list of core ids such as 0-7,16-23.",
+        default_value = "1"
+    )]
+    pub cores: String,
+
+    #[arg(
+        long,
+        help = "Skip SMT siblings so only one thread runs per physical core."
+    )]
+    pub skip_smt: bool,
+
+    #[arg(
+        long,
+        help = "Do not pin threads to cores. Allows a thread count above the number of cores."
+    )]
+    pub no_pin: bool,
+
+    #[arg(
+        long,
+        short,
+        value_name = "SECONDS",
+        help = "The number of seconds before the deadline to stop mining and start submitting.",
+        default_value = "5"
+    )]
+    pub buffer_time: u64,
+
+    #[arg(
+        long,
+        value_name = "LAMPORTS",
+        help = "Jito tip to assume per transaction. Defaults to the live tip when --jito is set."
+    )]
+    pub tip: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Price of 1 ORE in SOL, used to compute net earnings and the break-even priority fee."
+    )]
+    pub ore_price: Option<f64>,
+}
+
+#[derive(Parser, Debug)]
+pub struct HistoryArgs {
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only include rounds on or after this date (YYYY-MM-DD).",
+        value_parser = parse_start_of_day
+    )]
+    pub from: Option<i64>,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only include rounds on or before this date (YYYY-MM-DD).",
+        value_parser = parse_end_of_day
+    )]
+    pub to: Option<i64>,
+
+    #[arg(long, value_name = "KIND", help = "Only include solo or pool rounds.")]
+    pub kind: Option<RoundKind>,
+
+    #[arg(
+        long,
+        value_name = "STATUS",
+        help = "Only include confirmed or failed rounds."
+    )]
+    pub status: Option<RoundStatus>,
+
+    #[arg(
+        long,
+        short,
+        value_name = "COUNT",
+        help = "The number of most recent rounds to list.",
+        default_value = "20"
+    )]
+    pub limit: usize,
+}
+
 #[cfg(feature = "admin")]
 #[derive(Parser, Debug)]
 pub struct InitializeArgs {}
@@ -126,12 +237,24 @@ pub struct MineArgs {
     #[arg(
         long,
         short,
-        value_name = "CORES_COUNT",
-        help = "The number of CPU cores to allocate to mining.",
+        value_name = "CORES",
+        help = "The number of CPU cores to allocate to mining, or a list of core ids such as 0-7,16-23.",
         default_value = "1"
     )]
     pub cores: String,
 
+    #[arg(
+        long,
+        help = "Skip SMT siblings so only one thread runs per physical core."
+    )]
+    pub skip_smt: bool,
+
+    #[arg(
+        long,
+        help = "Do not pin threads to cores. Allows a thread count above the number of cores."
+    )]
+    pub no_pin: bool,
+
     #[arg(
         long,
         short,
@@ -141,21 +264,110 @@ pub struct MineArgs {
     )]
     pub buffer_time: u64,
 
+    #[arg(
+        long,
+        help = "Adjust the buffer time to measured landing latency. --buffer-time is used until enough rounds are observed."
+    )]
+    pub adaptive_buffer: bool,
+
+    #[arg(
+        long,
+        value_name = "DIFFICULTY",
+        help = "Submit as soon as a hash reaches this difficulty, without waiting for the deadline."
+    )]
+    pub target_difficulty: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Submit after this many seconds of hashing, whatever the best difficulty is."
+    )]
+    pub max_seconds: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "ORE",
+        help = "Claim the proof balance without prompting once it reaches this amount. Solo mining only."
+    )]
+    pub auto_claim_threshold: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "WALLET_ADDRESS",
+        help = "Wallet to send automatic claims to. Defaults to your own token account.",
+        requires = "auto_claim_threshold"
+    )]
+    pub auto_claim_to: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Warn, and top up if configured, when the fee payer balance falls below this amount. Solo mining only.",
+        default_value = "0.05"
+    )]
+    pub low_balance: f64,
+
+    #[arg(
+        long,
+        value_name = "KEYPAIR_FILEPATH",
+        help = "Keypair to top up the fee payer from when its balance is low."
+    )]
+    pub top_up_keypair: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Amount of SOL to send per top-up.",
+        default_value = "0.1"
+    )]
+    pub top_up_amount: f64,
+
+    #[arg(
+        long,
+        value_name = "SOL",
+        help = "Maximum amount of SOL to top up per day.",
+        default_value = "0.5"
+    )]
+    pub top_up_daily_limit: f64,
+
     #[arg(
         long,
         short,
         value_name = "DEVICE_ID",
-        help = "An optional device id to use for pool mining (max 5 devices per keypair)."
+        help = "An optional device id to use for pool mining (max 5 devices per keypair). Use \"auto\" to claim a free id shared with other miners on this host."
+    )]
+    pub device_id: Option<DeviceId>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Directory for the lock files used by --device-id auto. Defaults to a runtime directory on this host.",
+        requires = "device_id"
     )]
-    pub device_id: Option<u64>,
+    pub device_lock_dir: Option<String>,
 
     #[arg(
         long,
         short,
         value_name = "POOL_URL",
-        help = "The optional pool url to join and forward solutions to."
+        help = "The optional pool url to join and forward solutions to. Repeat or comma-separate several urls, in order of priority, to fail over between pools.",
+        value_delimiter = ','
     )]
-    pub pool_url: Option<String>,
+    pub pool_url: Vec<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "ACTION",
+        help = "Verify pool challenges against the pool's proof and the program config on-chain, and warn or stop when they do not match."
+    )]
+    pub verify_challenge: Option<ChallengeCheck>,
+
+    #[arg(
+        long,
+        help = "Mine against an in-process simulated chain (and pool, with --pool-url) without spending SOL."
+    )]
+    pub simulate: bool,
 
     #[arg(
         long,
@@ -164,6 +376,21 @@ pub struct MineArgs {
         default_value = "false"
     )]
     pub verbose: bool,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "An optional address to serve Prometheus metrics on, such as 127.0.0.1:9100."
+    )]
+    pub metrics_addr: Option<SocketAddr>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "How long to wait for an in-flight transaction to confirm after Ctrl-C before exiting.",
+        default_value = "30"
+    )]
+    pub shutdown_timeout: u64,
 }
 
 #[derive(Parser, Debug)]
@@ -171,6 +398,20 @@ pub struct PoolArgs {
     #[arg(value_name = "POOL_URL", help = "The pool url to connect to.")]
     pub pool_url: Option<String>,
 
+    #[arg(
+        long,
+        help = "Contact each pool server to measure latency and check the address it reports."
+    )]
+    pub probe: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SCORE",
+        help = "The score to rank pools by when listing them."
+    )]
+    pub sort: Option<PoolSort>,
+
     #[command(subcommand)]
     pub command: Option<PoolCommand>,
 }
@@ -179,10 +420,80 @@ pub struct PoolArgs {
 pub enum PoolCommand {
     #[command(about = "Commit a pending pool balance to the chain.")]
     Commit(PoolCommitArgs),
+
+    #[command(about = "Serve the pool to local miners, forwarding their work as a single member.")]
+    Proxy(PoolProxyArgs),
+
+    #[command(
+        about = "Compare pool, on-chain and recorded member earnings and flag discrepancies."
+    )]
+    Reconcile(PoolReconcileArgs),
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct PoolCommitArgs {
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        help = "Keep running and check the pending balance on this interval, e.g. 30m or 6h.",
+        value_parser = parse_interval
+    )]
+    pub every: Option<Duration>,
+
+    #[arg(
+        long,
+        value_name = "ORE",
+        help = "Only commit once the balance pending attribution on-chain reaches this amount.",
+        default_value = "0"
+    )]
+    pub min_pending: f64,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct PoolProxyArgs {
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "The address to serve local miners on. Pass 0.0.0.0:8080 to serve the whole network.",
+        default_value = "127.0.0.1:8080"
+    )]
+    pub listen: SocketAddr,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "The number of miners to split the member's nonce range between.",
+        default_value = "64"
+    )]
+    pub max_workers: u64,
 }
 
 #[derive(Parser, Clone, Debug)]
-pub struct PoolCommitArgs {}
+pub struct PoolReconcileArgs {
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only include rounds and claims on or after this date (YYYY-MM-DD).",
+        value_parser = parse_start_of_day
+    )]
+    pub from: Option<i64>,
+
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only include rounds and claims on or before this date (YYYY-MM-DD).",
+        value_parser = parse_end_of_day
+    )]
+    pub to: Option<i64>,
+
+    #[arg(
+        long,
+        value_name = "ORE",
+        help = "Differences up to this amount are not flagged.",
+        default_value = "0"
+    )]
+    pub tolerance: f64,
+}
 
 #[derive(Parser, Debug)]
 pub struct ProgramArgs {}
@@ -223,6 +534,11 @@ pub enum StakeCommand {
 
     #[command(about = "Get the list of stake accounts in a boost.")]
     Accounts(StakeAccountsArgs),
+
+    #[command(
+        about = "Periodically claim mining rewards and staking yield and deposit them as stake."
+    )]
+    Compound(StakeCompoundArgs),
 }
 
 #[derive(Parser, Clone, Debug)]
@@ -273,6 +589,26 @@ pub struct StakeWithdrawArgs {
     pub token_account: Option<String>,
 }
 
+#[derive(Parser, Clone, Debug)]
+pub struct StakeCompoundArgs {
+    #[arg(
+        long,
+        value_name = "ORE",
+        help = "The minimum amount of ORE to compound at once, so fees do not outweigh the deposit.",
+        default_value = "0.1"
+    )]
+    pub min_amount: f64,
+
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        help = "How long to wait between compounds, e.g. 30m or 6h.",
+        default_value = "1h",
+        value_parser = parse_interval
+    )]
+    pub interval: Duration,
+}
+
 #[derive(Parser, Clone, Debug)]
 pub struct StakeMigrateArgs {}
 
@@ -199,7 +199,7 @@ impl Miner {
 
         // Submit close transaction
         let ix = ore_api::sdk::close(signer.pubkey());
-        self.send_and_confirm(&[ix], ComputeBudget::Fixed(500_000), false)
+        self.send_and_confirm(&[ix], ComputeBudget::Dynamic(500_000), false)
             .await
             .ok();
     }
@@ -1,76 +1,49 @@
-use std::{sync::Arc, time::Instant};
+use std::time::{Duration, Instant};
 
-use drillx::equix;
+use colored::*;
+use indicatif::ProgressBar;
 use solana_rpc_client::spinner;
 
-use crate::{args::BenchmarkArgs, Miner};
+use crate::{
+    args::BenchmarkArgs,
+    utils::select_cores,
+    worker::{Job, StopPolicy, WorkerPool},
+    Miner,
+};
 
 const TEST_DURATION: i64 = 30;
 
 impl Miner {
     pub async fn benchmark(&self, args: BenchmarkArgs) {
         // Check num threads
-        let cores = self.parse_cores(args.cores);
-        self.check_num_cores(cores);
+        let cores = match select_cores(&args.cores, args.skip_smt, args.no_pin) {
+            Ok(cores) => cores,
+            Err(err) => {
+                println!("{} {}", "ERROR".bold().red(), err);
+                return;
+            }
+        };
+        let workers = WorkerPool::new(cores);
 
         // Dispatch job to each thread
-        let challenge = [0; 32];
-        let progress_bar = Arc::new(spinner::new_progress_bar());
+        let progress_bar = spinner::new_progress_bar();
         progress_bar.set_message(format!(
             "Benchmarking. This will take {} sec...",
             TEST_DURATION
         ));
-        let core_ids = core_affinity::get_core_ids().expect("Failed to fetch core count");
-        let handles: Vec<_> = core_ids
-            .into_iter()
-            .map(|i| {
-                std::thread::spawn({
-                    move || {
-                        let timer = Instant::now();
-                        let first_nonce = u64::MAX
-                            .saturating_div(cores)
-                            .saturating_mul(i.id as u64);
-                        let mut nonce = first_nonce;
-                        let mut memory = equix::SolverMemory::new();
-                        loop {
-                            // Return if core should not be used
-                            if (i.id as u64).ge(&cores) {
-                                return 0;
-                            }
-
-                            // Pin to core
-                            let _ = core_affinity::set_for_current(i);
-
-                            // Create hash
-                            let _hx = drillx::hash_with_memory(
-                                &mut memory,
-                                &challenge,
-                                &nonce.to_le_bytes(),
-                            );
-
-                            // Increment nonce
-                            nonce += 1;
-
-                            // Exit if time has elapsed
-                            if (timer.elapsed().as_secs() as i64).ge(&TEST_DURATION) {
-                                break;
-                            }
-                        }
-
-                        // Return hash count
-                        nonce - first_nonce
-                    }
-                })
-            })
-            .collect();
-
-        // Join handles and return best nonce
-        let mut total_nonces = 0;
-        for h in handles {
-            if let Ok(count) = h.join() {
-                total_nonces += count;
-            }
-        }
+        let num_workers = workers.len() as u64;
+        let job = Job {
+            challenge: [0; 32],
+            nonces: (0..num_workers)
+                .map(|n| u64::MAX.saturating_div(num_workers).saturating_mul(n))
+                .collect(),
+            deadline: Instant::now() + Duration::from_secs(TEST_DURATION as u64),
+            min_difficulty: 0,
+            improvements: None,
+            policy: StopPolicy::default(),
+        };
+        let result = workers.run(job, &ProgressBar::hidden()).await;
+        let total_nonces: u64 = result.worker_hashes.iter().sum();
 
         // Update log
         progress_bar.finish_with_message(format!(
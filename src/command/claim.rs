@@ -2,7 +2,8 @@ use std::str::FromStr;
 
 use colored::*;
 use ore_api::consts::MINT_ADDRESS;
-use solana_program::pubkey::Pubkey;
+use solana_client::client_error::Result as ClientResult;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
 use solana_sdk::signature::{Signature, Signer};
 use spl_token::amount_to_ui_amount;
 
@@ -18,10 +19,7 @@ impl Miner {
     pub async fn claim(&self, args: ClaimArgs) -> Result<(), crate::error::Error> {
         match args.pool_url {
             Some(ref pool_url) => {
-                let pool = &Pool {
-                    http_client: reqwest::Client::new(),
-                    pool_url: pool_url.clone(),
-                };
+                let pool = &Pool::new(pool_url.clone());
                 let _ = self.claim_from_pool(args, pool).await?;
                 Ok(())
             }
@@ -35,35 +33,9 @@ impl Miner {
     pub async fn claim_from_proof(&self, args: ClaimArgs) {
         let signer = self.signer();
         let pubkey = signer.pubkey();
-        let proof = get_proof_with_authority(&self.rpc_client, pubkey).await.expect("Failed to fetch proof account");
-        let mut ixs = vec![];
-        let beneficiary = match args.to {
-            None => self.initialize_ata(pubkey).await,
-            Some(to) => {
-                // Create beneficiary token account, if needed
-                let wallet = Pubkey::from_str(&to).expect("Failed to parse wallet address");
-                let benefiary_tokens = spl_associated_token_account::get_associated_token_address(
-                    &wallet,
-                    &MINT_ADDRESS,
-                );
-                if self
-                    .rpc_client
-                    .get_token_account(&benefiary_tokens)
-                    .await
-                    .is_err()
-                {
-                    ixs.push(
-                        spl_associated_token_account::instruction::create_associated_token_account(
-                            &pubkey,
-                            &wallet,
-                            &ore_api::consts::MINT_ADDRESS,
-                            &spl_token::id(),
-                        ),
-                    );
-                }
-                benefiary_tokens
-            }
-        };
+        let proof = get_proof_with_authority(&self.rpc_client, pubkey)
+            .await
+            .expect("Failed to fetch proof account");
 
         // Parse amount to claim
         let amount = if let Some(amount) = args.amount {
@@ -88,10 +60,24 @@ impl Miner {
         }
 
         // Send and confirm
-        ixs.push(ore_api::sdk::claim(pubkey, beneficiary, amount));
-        self.send_and_confirm(&ixs, ComputeBudget::Fixed(32_000), false)
+        self.send_claim_from_proof(args.to, amount).await.ok();
+    }
+
+    /// Claims `amount` from the signer's proof without prompting.
+    pub async fn send_claim_from_proof(
+        &self,
+        to: Option<String>,
+        amount: u64,
+    ) -> ClientResult<Signature> {
+        let mut ixs = vec![];
+        let beneficiary = self.claim_beneficiary(to, &mut ixs).await;
+        ixs.push(ore_api::sdk::claim(
+            self.signer().pubkey(),
+            beneficiary,
+            amount,
+        ));
+        self.send_and_confirm(&ixs, ComputeBudget::Dynamic(32_000), false)
             .await
-            .ok();
     }
 
     async fn claim_from_pool(
@@ -103,35 +89,6 @@ impl Miner {
         let member = pool
             .get_pool_member_onchain(self, pool_address.address)
             .await?;
-        let mut ixs = vec![];
-
-        // Create beneficiary token account, if needed
-        let beneficiary = match args.to {
-            None => self.initialize_ata(self.signer().pubkey()).await,
-            Some(to) => {
-                let wallet = Pubkey::from_str(&to).expect("Failed to parse wallet address");
-                let benefiary_tokens = spl_associated_token_account::get_associated_token_address(
-                    &wallet,
-                    &MINT_ADDRESS,
-                );
-                if self
-                    .rpc_client
-                    .get_token_account(&benefiary_tokens)
-                    .await
-                    .is_err()
-                {
-                    ixs.push(
-                        spl_associated_token_account::instruction::create_associated_token_account(
-                            &self.signer().pubkey(),
-                            &wallet,
-                            &ore_api::consts::MINT_ADDRESS,
-                            &spl_token::id(),
-                        ),
-                    );
-                }
-                benefiary_tokens
-            }
-        };
 
         // Parse amount to claim
         let amount = if let Some(amount) = args.amount {
@@ -156,15 +113,61 @@ impl Miner {
         }
 
         // Send and confirm
+        self.send_claim_from_pool(args.to, amount, pool_address.address)
+            .await
+            .map_err(From::from)
+    }
+
+    /// Claims `amount` from the signer's member account in the pool at `pool_address`
+    /// without prompting.
+    pub async fn send_claim_from_pool(
+        &self,
+        to: Option<String>,
+        amount: u64,
+        pool_address: Pubkey,
+    ) -> ClientResult<Signature> {
+        let mut ixs = vec![];
+        let beneficiary = self.claim_beneficiary(to, &mut ixs).await;
         ixs.push(ore_pool_api::sdk::claim(
             self.signer().pubkey(),
             beneficiary,
-            pool_address.address,
+            pool_address,
             amount,
         ));
-        self.send_and_confirm(&ixs, ComputeBudget::Fixed(50_000), false)
+        self.send_and_confirm(&ixs, ComputeBudget::Dynamic(50_000), false)
             .await
-            .map_err(From::from)
+    }
+
+    /// Returns the token account to claim into, queueing its creation in `ixs` if needed.
+    /// Defaults to the signer's own token account.
+    async fn claim_beneficiary(&self, to: Option<String>, ixs: &mut Vec<Instruction>) -> Pubkey {
+        match to {
+            None => self.initialize_ata(self.signer().pubkey()).await,
+            Some(to) => {
+                // Create beneficiary token account, if needed
+                let wallet = Pubkey::from_str(&to).expect("Failed to parse wallet address");
+                let benefiary_tokens = spl_associated_token_account::get_associated_token_address(
+                    &wallet,
+                    &MINT_ADDRESS,
+                );
+                if self
+                    .rpc_client
+                    .get_token_account(&benefiary_tokens)
+                    .await
+                    .is_err()
+                {
+                    ixs.push(
+                        spl_associated_token_account::instruction::create_associated_token_account(
+                            &self.signer().pubkey(),
+                            &wallet,
+                            &ore_api::consts::MINT_ADDRESS,
+                            &spl_token::id(),
+                        ),
+                    );
+                }
+                benefiary_tokens
+            }
+        }
     }
 
     pub async fn initialize_ata(&self, wallet: Pubkey) -> Pubkey {
@@ -189,7 +192,7 @@ impl Miner {
             &ore_api::consts::MINT_ADDRESS,
             &spl_token::id(),
         );
-        self.send_and_confirm(&[ix], ComputeBudget::Fixed(400_000), false)
+        self.send_and_confirm(&[ix], ComputeBudget::Dynamic(400_000), false)
             .await
             .ok();
 
@@ -0,0 +1,297 @@
+use std::time::{Duration, Instant};
+
+use colored::*;
+use drillx::Solution;
+use indicatif::ProgressBar;
+use ore_api::{
+    consts::{ONE_MINUTE, ONE_ORE},
+    state::{proof_pda, Config},
+};
+use solana_program::native_token::{lamports_to_sol, LAMPORTS_PER_SOL};
+use solana_rpc_client::spinner;
+use solana_sdk::signature::Signer;
+use tabled::{
+    settings::{
+        object::{Columns, Rows},
+        Alignment, Remove, Style,
+    },
+    Table,
+};
+
+use crate::{
+    args::EstimateArgs,
+    utils::{
+        amount_u64_to_f64, expected_reward, get_config, get_proof_with_authority, reward_rates,
+        select_cores, TableData, TableSectionTitle,
+    },
+    worker::{Job, StopPolicy, WorkerPool},
+    Miner,
+};
+
+/// Seconds spent measuring the hashrate when none is given.
+const MEASURE_DURATION: u64 = 10;
+
+/// Compute budget requested by solo mine transactions that cannot be simulated.
+const COMPUTE_UNITS: u64 = 750_000;
+
+/// How long to wait for the live Jito tip when none is given.
+const TIP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to hash for a solution to simulate a mine transaction with.
+const SOLUTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Lamports charged per transaction signature.
+const SIGNATURE_FEE: u64 = 5_000;
+
+/// Mining rounds in a day, assuming one transaction lands per minute.
+const ROUNDS_PER_DAY: f64 = 24.0 * 60.0;
+
+impl Miner {
+    pub async fn estimate(&self, args: EstimateArgs, jito: bool) {
+        // Measure hashrate
+        let hashrate = match args.hashrate {
+            Some(hashrate) => hashrate,
+            None => match self.measure_hashrate(&args).await {
+                Some(hashrate) => hashrate,
+                None => return,
+            },
+        };
+
+        // Expected reward per round
+        let config = get_config(&self.rpc_client).await;
+        let rates = reward_rates(&config);
+        let hashing_time = (ONE_MINUTE as u64).saturating_sub(args.buffer_time);
+        let hashes = hashrate as f64 * hashing_time as f64;
+        let reward = expected_reward(&rates, hashes) / ONE_ORE as f64;
+
+        // Cost per round
+        let priority_fee = self.estimate_priority_fee().await;
+        let compute_units = self.estimate_mine_compute_units(&config).await;
+        let tip = match (args.tip, jito) {
+            (Some(tip), _) => tip,
+            (None, false) => 0,
+            (None, true) => match self.live_tip().await {
+                Some(tip) => tip,
+                None => {
+                    println!(
+                        "{} No live Jito tip was available. Set one with --tip.",
+                        "ERROR".bold().red()
+                    );
+                    return;
+                }
+            },
+        };
+        let fixed_cost = SIGNATURE_FEE + tip;
+        let cost = fixed_cost + priority_fee.saturating_mul(compute_units) / 1_000_000;
+
+        // Aggregate data
+        let mut data = vec![];
+        data.push(TableData {
+            key: "Hashrate".to_string(),
+            value: format!("{} H/sec", hashrate),
+        });
+        data.push(TableData {
+            key: "Hashes per round".to_string(),
+            value: format!("{:.0}", hashes),
+        });
+        data.push(TableData {
+            key: "Min difficulty".to_string(),
+            value: config.min_difficulty.to_string(),
+        });
+        data.push(TableData {
+            key: "Base reward rate".to_string(),
+            value: format!("{:#.11} ORE", amount_u64_to_f64(config.base_reward_rate)),
+        });
+        let len1 = data.len();
+        data.push(TableData {
+            key: "Per round".to_string(),
+            value: format!("{:#.11} ORE", reward),
+        });
+        data.push(TableData {
+            key: "Per hour".to_string(),
+            value: format!("{:#.11} ORE", reward * 60.0),
+        });
+        data.push(TableData {
+            key: "Per day".to_string(),
+            value: format!("{:#.11} ORE", reward * ROUNDS_PER_DAY),
+        });
+        let len2 = data.len();
+        data.push(TableData {
+            key: "Compute units".to_string(),
+            value: compute_units.to_string(),
+        });
+        data.push(TableData {
+            key: "Priority fee".to_string(),
+            value: format!("{} microlamports", priority_fee),
+        });
+        data.push(TableData {
+            key: "Jito tip".to_string(),
+            value: format!("{} SOL", lamports_to_sol(tip)),
+        });
+        data.push(TableData {
+            key: "Per transaction".to_string(),
+            value: format!("{} SOL", lamports_to_sol(cost)),
+        });
+        data.push(TableData {
+            key: "Per day".to_string(),
+            value: format!("{:.9} SOL", lamports_to_sol(cost) * ROUNDS_PER_DAY),
+        });
+        let len3 = data.len();
+        if let Some(ore_price) = args.ore_price {
+            let revenue = reward * ore_price * LAMPORTS_PER_SOL as f64;
+            let net = (revenue - cost as f64) * ROUNDS_PER_DAY;
+            let break_even = (revenue - fixed_cost as f64) * 1e6 / compute_units as f64;
+            data.push(TableData {
+                key: "Net per day".to_string(),
+                value: format!("{:.9} SOL", net / LAMPORTS_PER_SOL as f64),
+            });
+            data.push(TableData {
+                key: "Break-even fee".to_string(),
+                value: if break_even > 0.0 {
+                    format!("{:.0} microlamports", break_even)
+                } else {
+                    "Unprofitable at any fee".to_string()
+                },
+            });
+        }
+
+        // Build table
+        let mut table = Table::new(data);
+        table.with(Remove::row(Rows::first()));
+        table.modify(Columns::single(1), Alignment::right());
+        table.with(Style::blank());
+        table.section_title(0, "Machine");
+        table.section_title(len1, "Expected earnings");
+        table.section_title(len2, "Costs");
+        if args.ore_price.is_some() {
+            table.section_title(len3, "Profitability");
+        }
+        println!("{table}\n");
+        if args.ore_price.is_none() {
+            println!("Set --ore-price to see net earnings and the break-even priority fee.");
+        }
+    }
+
+    async fn measure_hashrate(&self, args: &EstimateArgs) -> Option<u64> {
+        let cores = match select_cores(&args.cores, args.skip_smt, args.no_pin) {
+            Ok(cores) => cores,
+            Err(err) => {
+                println!("{} {}", "ERROR".bold().red(), err);
+                return None;
+            }
+        };
+        let workers = WorkerPool::new(cores);
+        let progress_bar = spinner::new_progress_bar();
+        progress_bar.set_message(format!(
+            "Measuring hashrate. This will take {} sec...",
+            MEASURE_DURATION
+        ));
+        let num_workers = workers.len() as u64;
+        let job = Job {
+            challenge: [0; 32],
+            nonces: (0..num_workers)
+                .map(|n| u64::MAX.saturating_div(num_workers).saturating_mul(n))
+                .collect(),
+            deadline: Instant::now() + Duration::from_secs(MEASURE_DURATION),
+            min_difficulty: 0,
+            improvements: None,
+            policy: StopPolicy::default(),
+        };
+        let result = workers.run(job, &ProgressBar::hidden()).await;
+        let total_nonces: u64 = result.worker_hashes.iter().sum();
+        progress_bar.finish_and_clear();
+        Some((total_nonces as f64 / result.elapsed.as_secs_f64().max(f64::EPSILON)) as u64)
+    }
+
+    /// Waits for the Jito tip stream to report a tip. Returns None if it does not in time.
+    async fn live_tip(&self) -> Option<u64> {
+        let deadline = Instant::now() + TIP_TIMEOUT;
+        loop {
+            let tip = *self.tip.read().unwrap();
+            if tip > 0 {
+                return Some(tip);
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// The compute units a solo mine transaction would request, sized to a simulation the
+    /// same way `send_and_confirm` sizes them.
+    async fn estimate_mine_compute_units(&self, config: &Config) -> u64 {
+        match self.simulate_mine(config).await {
+            Ok(compute_units) => compute_units as u64,
+            Err(err) => {
+                println!(
+                    "{} {}. Falling back to static value: {} compute units",
+                    "WARNING".bold().yellow(),
+                    err,
+                    COMPUTE_UNITS
+                );
+                COMPUTE_UNITS
+            }
+        }
+    }
+
+    async fn simulate_mine(&self, config: &Config) -> Result<u32, String> {
+        // Find a solution to the signer's challenge
+        let authority = self.signer().pubkey();
+        let proof = get_proof_with_authority(&self.rpc_client, authority)
+            .await
+            .map_err(|_| "No proof account to simulate mining with".to_string())?;
+        let min_difficulty = config.min_difficulty as u32;
+        let solution = tokio::task::spawn_blocking(move || {
+            find_solution(&proof.challenge, min_difficulty, SOLUTION_TIMEOUT)
+        })
+        .await
+        .ok()
+        .flatten()
+        .ok_or("No solution found to simulate mining with")?;
+
+        // Simulate the mine instructions
+        let ixs = [
+            ore_api::sdk::auth(proof_pda(authority).0),
+            ore_api::sdk::mine(
+                authority,
+                authority,
+                self.find_bus().await,
+                solution,
+                ore_boost_api::state::config_pda().0,
+            ),
+        ];
+        self.estimate_compute_units(&ixs).await
+    }
+
+    async fn estimate_priority_fee(&self) -> u64 {
+        if !self.dynamic_fee {
+            return self.priority_fee.unwrap_or(0);
+        }
+        match self.get_dynamic_priority_fee().await {
+            Ok(fee) => fee,
+            Err(err) => {
+                let fee = self.priority_fee.unwrap_or(0);
+                println!(
+                    "{} {}. Falling back to static value: {} microlamports",
+                    "WARNING".bold().yellow(),
+                    err,
+                    fee
+                );
+                fee
+            }
+        }
+    }
+}
+
+/// Hashes `challenge` until a solution reaches `min_difficulty`, giving up after `timeout`.
+fn find_solution(challenge: &[u8; 32], min_difficulty: u32, timeout: Duration) -> Option<Solution> {
+    let deadline = Instant::now() + timeout;
+    (0u64..)
+        .take_while(|_| Instant::now() < deadline)
+        .find_map(|nonce| {
+            let hash = drillx::hash(challenge, &nonce.to_le_bytes()).ok()?;
+            (hash.difficulty() >= min_difficulty)
+                .then(|| Solution::new(hash.d, nonce.to_le_bytes()))
+        })
+}
@@ -0,0 +1,221 @@
+use colored::*;
+use solana_program::native_token::lamports_to_sol;
+use tabled::{
+    settings::{
+        object::{Columns, Rows},
+        style::BorderColor,
+        Alignment, Border, Color, Highlight, Remove, Style,
+    },
+    Table, Tabled,
+};
+
+use crate::{
+    args::HistoryArgs,
+    utils::{
+        amount_u64_to_f64, format_timestamp, JournalEntry, RoundKind, RoundStatus, TableData,
+        TableSectionTitle,
+    },
+    Miner,
+};
+
+impl Miner {
+    pub fn history(&self, args: HistoryArgs) {
+        // Read journal
+        let entries = match self.journal.read() {
+            Ok(entries) => entries,
+            Err(err) => {
+                println!(
+                    "{} Failed to read journal at {}: {}",
+                    "ERROR".bold().red(),
+                    self.journal.path.display(),
+                    err
+                );
+                return;
+            }
+        };
+
+        // Filter entries
+        let entries: Vec<JournalEntry> = entries
+            .into_iter()
+            .filter(|e| args.from.map_or(true, |from| e.timestamp >= from))
+            .filter(|e| args.to.map_or(true, |to| e.timestamp <= to))
+            .filter(|e| args.kind.map_or(true, |kind| e.kind == kind))
+            .filter(|e| args.status.map_or(true, |status| e.status == status))
+            .collect();
+        if entries.is_empty() {
+            println!("No mining rounds found in {}", self.journal.path.display());
+            return;
+        }
+
+        // Print most recent rounds
+        let rows: Vec<HistoryTableData> = entries
+            .iter()
+            .rev()
+            .take(args.limit)
+            .map(HistoryTableData::from)
+            .collect();
+        let mut table = Table::new(&rows);
+        table.with(Style::blank());
+        table.modify(Columns::new(1..), Alignment::right());
+        table.modify(Rows::first(), Color::BOLD);
+        table.with(
+            Highlight::new(Rows::single(1)).color(BorderColor::default().top(Color::FG_WHITE)),
+        );
+        table.with(Highlight::new(Rows::single(1)).border(Border::new().top('━')));
+        println!("\n{}\n", table);
+
+        // Print summary
+        let data = summarize(&entries);
+        let mut table = Table::new(data);
+        table.with(Remove::row(Rows::first()));
+        table.modify(Columns::single(1), Alignment::right());
+        table.with(Style::blank());
+        table.section_title(0, "Summary");
+        println!("{table}\n");
+    }
+}
+
+fn summarize(entries: &[JournalEntry]) -> Vec<TableData> {
+    let confirmed: Vec<&JournalEntry> = entries
+        .iter()
+        .filter(|e| e.status == RoundStatus::Confirmed)
+        .collect();
+    let failed = entries.len() - confirmed.len();
+    let total_base: u64 = confirmed
+        .iter()
+        .filter(|e| e.kind == RoundKind::Solo)
+        .map(|e| e.base_reward)
+        .sum();
+    let total_boost: u64 = confirmed
+        .iter()
+        .filter(|e| e.kind == RoundKind::Solo)
+        .map(|e| e.boost_reward)
+        .sum();
+    let total_reward: u64 = confirmed.iter().map(|e| e.my_reward()).sum();
+    let total_fees: u64 = entries.iter().map(|e| e.fee).sum();
+    let total_tips: u64 = confirmed.iter().map(|e| e.tip).sum();
+    let best_difficulty = confirmed.iter().map(|e| e.my_difficulty()).max();
+    let (avg_difficulty, avg_reward) = if confirmed.is_empty() {
+        (0f64, 0f64)
+    } else {
+        (
+            confirmed.iter().map(|e| e.my_difficulty()).sum::<u64>() as f64
+                / confirmed.len() as f64,
+            amount_u64_to_f64(total_reward) / confirmed.len() as f64,
+        )
+    };
+    let first = entries
+        .iter()
+        .map(|e| e.timestamp)
+        .min()
+        .unwrap_or_default();
+    let last = entries
+        .iter()
+        .map(|e| e.timestamp)
+        .max()
+        .unwrap_or_default();
+    vec![
+        TableData {
+            key: "Period".to_string(),
+            value: format!("{} – {}", format_timestamp(first), format_timestamp(last)),
+        },
+        TableData {
+            key: "Rounds".to_string(),
+            value: entries.len().to_string(),
+        },
+        TableData {
+            key: "Confirmed".to_string(),
+            value: confirmed.len().to_string(),
+        },
+        TableData {
+            key: "Failed".to_string(),
+            value: failed.to_string(),
+        },
+        TableData {
+            key: "Base reward (solo)".to_string(),
+            value: format!("{:#.11} ORE", amount_u64_to_f64(total_base)),
+        },
+        TableData {
+            key: "Boost reward (solo)".to_string(),
+            value: format!("{:#.11} ORE", amount_u64_to_f64(total_boost)),
+        },
+        TableData {
+            key: "Total reward".to_string(),
+            value: format!("{:#.11} ORE", amount_u64_to_f64(total_reward))
+                .bold()
+                .yellow()
+                .to_string(),
+        },
+        TableData {
+            key: "Average reward".to_string(),
+            value: format!("{:#.11} ORE", avg_reward),
+        },
+        TableData {
+            key: "Average score".to_string(),
+            value: format!("{:.2}", avg_difficulty),
+        },
+        TableData {
+            key: "Best score".to_string(),
+            value: best_difficulty.map_or("–".to_string(), |d| d.to_string()),
+        },
+        TableData {
+            key: "Fees paid".to_string(),
+            value: format!("{} SOL", lamports_to_sol(total_fees)),
+        },
+        TableData {
+            key: "Tips paid".to_string(),
+            value: format!("{} SOL", lamports_to_sol(total_tips)),
+        },
+    ]
+}
+
+#[derive(Tabled)]
+pub struct HistoryTableData {
+    #[tabled(rename = "Timestamp")]
+    pub timestamp: String,
+    #[tabled(rename = "Kind")]
+    pub kind: String,
+    #[tabled(rename = "Signature")]
+    pub signature: String,
+    #[tabled(rename = "Score")]
+    pub difficulty: String,
+    #[tabled(rename = "Reward")]
+    pub reward: String,
+    #[tabled(rename = "Fee")]
+    pub fee: String,
+    #[tabled(rename = "Status")]
+    pub status: String,
+}
+
+impl From<&JournalEntry> for HistoryTableData {
+    fn from(entry: &JournalEntry) -> Self {
+        let confirmed = entry.status == RoundStatus::Confirmed;
+        Self {
+            timestamp: format_timestamp(entry.timestamp),
+            kind: match entry.kind {
+                RoundKind::Solo => "Solo".to_string(),
+                RoundKind::Pool => "Pool".to_string(),
+            },
+            signature: entry
+                .signature
+                .as_ref()
+                .map_or("–".to_string(), |sig| format!("{}...", &sig[..8])),
+            difficulty: if confirmed {
+                entry.my_difficulty().to_string()
+            } else {
+                "–".to_string()
+            },
+            reward: if confirmed {
+                format!("{:#.11}", amount_u64_to_f64(entry.my_reward()))
+            } else {
+                "–".to_string()
+            },
+            fee: format!("{} SOL", lamports_to_sol(entry.fee + entry.tip)),
+            status: if confirmed {
+                "Confirmed".bold().green().to_string()
+            } else {
+                "Failed".bold().red().to_string()
+            },
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use ore_api::consts::TREASURY_ADDRESS;
 use solana_sdk::signature::Signer;
 
-use crate::{Miner, utils::ComputeBudget};
+use crate::{utils::ComputeBudget, Miner};
 
 impl Miner {
     pub async fn initialize(&self) {
@@ -12,6 +12,9 @@ impl Miner {
 
         // Submit initialize tx
         let ix = ore_api::sdk::initialize(self.signer().pubkey());
-        let _ = self.send_and_confirm(&[ix], ComputeBudget::Fixed(500_000), false).await.unwrap();
+        let _ = self
+            .send_and_confirm(&[ix], ComputeBudget::Dynamic(500_000), false)
+            .await
+            .unwrap();
     }
 }
@@ -1,6 +1,8 @@
 use std::{
     io::stdout,
-    sync::{Arc, RwLock},
+    path::PathBuf,
+    str::FromStr,
+    sync::{atomic::Ordering, Arc, Mutex},
     thread::sleep,
     time::{Duration, Instant},
     usize,
@@ -13,19 +15,24 @@ use crossterm::{
     execute,
     terminal::{Clear, ClearType},
 };
-use drillx::{
-    equix::{self},
-    Hash, Solution,
-};
+use drillx::Solution;
 use ore_api::{
     consts::{BUS_ADDRESSES, BUS_COUNT, EPOCH_DURATION},
     event::MineEvent,
     state::{proof_pda, Bus, Config},
 };
+use ore_pool_types::MemberChallenge;
 use rand::Rng;
-use solana_program::pubkey::Pubkey;
-use solana_rpc_client::spinner;
-use solana_sdk::{signature::Signature, signer::Signer};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client::RpcClientConfig};
+use solana_program::{
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, write_keypair_file, Keypair, Signature},
+    signer::Signer,
+};
 use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
 use steel::AccountDeserialize;
 use tabled::{
@@ -40,44 +47,136 @@ use tabled::{
 use crate::{
     args::MineArgs,
     error::Error,
+    pool_client::PoolFeed,
+    send::{TopUp, Watchdog},
+    simulate::{Chain, SimulatedPool, SimulatedRpc},
     utils::{
-        amount_u64_to_f64, format_duration, format_timestamp, get_clock, get_config,
-        get_updated_proof_with_authority, ComputeBudget, PoolMiningData, SoloMiningData,
+        amount_f64_to_u64, amount_u64_to_f64, default_device_lock_dir, format_duration,
+        format_timestamp, get_clock, get_config, get_updated_proof_with_authority, select_cores,
+        serve_metrics, verify_pool_challenge, ChallengeCheck, ComputeBudget, DeviceId, DeviceLock,
+        Journal, JournalEntry, LogFormat, Notifier, NotifyEvent, PoolMiningData, RoundKind,
+        RoundStatus, SoloMiningData, TableData, TableSectionTitle,
     },
+    worker::{Job, StopPolicy, StopReason, WorkerPool},
     Miner,
 };
 
-use super::pool::Pool;
+use super::pool::{Pool, PoolSet, SolutionPipeline};
+
+/// How long to wait for the active pool to serve a new challenge before counting a failure.
+const CHALLENGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long to wait for a pool to push a new challenge before dropping its feed and polling.
+/// Challenges are pushed alongside the previous round's event, so a live feed has one ready.
+const FEED_CHALLENGE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to wait for a pool to push a mining event before polling for it.
+const EVENT_TIMEOUT: Duration = Duration::from_secs(15);
 
 impl Miner {
     pub async fn mine(&self, args: MineArgs) -> Result<(), Error> {
-        match args.pool_url {
-            Some(ref pool_url) => {
-                let pool = &Pool {
-                    http_client: reqwest::Client::new(),
-                    pool_url: pool_url.clone(),
-                };
-                self.mine_pool(args, pool).await?;
-            }
-            None => {
-                self.mine_solo(args).await;
-            }
+        // Swap in simulated endpoints, if requested
+        if args.simulate {
+            let (miner, pools) = self.simulated(&args)?;
+            return miner.mine_with(args, pools).await;
         }
-        Ok(())
+        let pools = args.pool_url.iter().cloned().map(Pool::new).collect();
+        self.mine_with(args, pools).await
     }
 
-    async fn mine_solo(&self, args: MineArgs) {
-        // Open account, if needed.
-        self.open().await;
+    /// Mines solo, or with `pools` in order of priority if there are any.
+    async fn mine_with(&self, args: MineArgs, pools: Vec<Pool>) -> Result<(), Error> {
+        // Serve metrics, if requested
+        if let Some(metrics_addr) = args.metrics_addr {
+            tokio::spawn(serve_metrics(metrics_addr, self.metrics.clone()));
+        }
 
-        // Check num threads
-        let cores_str = args.cores;
-        let cores = if cores_str == "ALL" {
-            num_cpus::get() as u64
+        // Stop gracefully on Ctrl-C, reporting the session even if the round does not finish
+        let session_start = Instant::now();
+        let miner = self.clone();
+        self.shutdown
+            .listen(Duration::from_secs(args.shutdown_timeout), move || {
+                miner.print_session_summary(session_start.elapsed())
+            });
+
+        // Check auto-claim wallet before mining
+        if let Some(ref to) = args.auto_claim_to {
+            Pubkey::from_str(to)?;
+        }
+
+        // Load fee payer watchdog
+        let watchdog = self.watchdog(&args)?;
+
+        // Start hashing workers
+        let cores = select_cores(&args.cores, args.skip_smt, args.no_pin)?;
+        let workers = WorkerPool::new(cores);
+
+        let result = if pools.is_empty() {
+            self.mine_solo(args, &workers, &watchdog).await;
+            Ok(())
         } else {
-            cores_str.parse::<u64>().unwrap()
+            let mut pools = PoolSet::new(pools);
+            self.mine_pool(args, &mut pools, &workers).await
         };
-        self.check_num_cores(cores);
+
+        // Print session report
+        self.print_session_summary(session_start.elapsed());
+        result
+    }
+
+    fn simulated(&self, args: &MineArgs) -> Result<(Miner, Vec<Pool>), Error> {
+        // Use the configured keypair if there is one. Nothing is signed for a real chain.
+        let keypair_filepath = match self.keypair_filepath.clone() {
+            Some(filepath) if read_keypair_file(&filepath).is_ok() => filepath,
+            _ => {
+                let filepath = std::env::temp_dir().join("ore-simulated-keypair.json");
+                write_keypair_file(&Keypair::new(), &filepath)
+                    .map_err(|err| Error::Internal(err.to_string()))?;
+                filepath.to_string_lossy().to_string()
+            }
+        };
+        let authority = read_keypair_file(&keypair_filepath)
+            .map_err(|err| Error::Internal(err.to_string()))?
+            .pubkey();
+
+        // Serve chain and pool from memory
+        let chain = Arc::new(Mutex::new(Chain::new(authority)));
+        let rpc_client = Arc::new(RpcClient::new_sender(
+            SimulatedRpc::new(chain.clone()),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        ));
+        let pools = args
+            .pool_url
+            .iter()
+            .map(|pool_url| {
+                let pool = SimulatedPool::new(pool_url.clone(), chain.clone());
+                Pool::with_client(Arc::new(pool))
+            })
+            .collect();
+
+        // Keep simulated rounds out of the real journal and notifications
+        let journal = Journal::new(self.journal.path.with_extension("simulated.jsonl"));
+        println!(
+            "{} Simulating mining. Rounds are journaled to {}",
+            "INFO".bold().cyan(),
+            journal.path.display()
+        );
+        let miner = Miner {
+            rpc_client: rpc_client.clone(),
+            jito_client: rpc_client,
+            keypair_filepath: Some(keypair_filepath.clone()),
+            fee_payer_filepath: Some(keypair_filepath),
+            dynamic_fee: false,
+            journal,
+            notifier: Arc::new(Notifier::default()),
+            ..self.clone()
+        };
+        Ok((miner, pools))
+    }
+
+    async fn mine_solo(&self, args: MineArgs, workers: &WorkerPool, watchdog: &Watchdog) {
+        // Open account, if needed.
+        self.open().await;
 
         // Get verbose flag
         let verbose = args.verbose;
@@ -89,13 +188,22 @@ impl Miner {
 
         // Start mining loop
         let mut last_hash_at = 0;
-        loop {
+        while !self.shutdown.is_requested() {
+            // Pause while the fee payer cannot afford to submit
+            if !self.wait_for_balance(watchdog).await {
+                break;
+            }
+
             // Fetch accounts
             let config = get_config(&self.rpc_client).await;
-            let proof =
-                get_updated_proof_with_authority(&self.rpc_client, signer.pubkey(), last_hash_at)
-                    .await
-                    .expect("Failed to fetch proof account");
+            let proof = tokio::select! {
+                proof = get_updated_proof_with_authority(
+                    &self.rpc_client,
+                    signer.pubkey(),
+                    last_hash_at,
+                ) => proof.expect("Failed to fetch proof account"),
+                _ = self.shutdown.wait() => break,
+            };
 
             // Log mining table
             self.update_solo_mining_table(verbose);
@@ -103,10 +211,20 @@ impl Miner {
             // Track timestamp
             last_hash_at = proof.last_hash_at;
 
+            // Claim rewards between rounds, if over threshold
+            if let Some(threshold) = args.auto_claim_threshold {
+                if proof.balance > 0 && proof.balance >= amount_f64_to_u64(threshold) {
+                    self.auto_claim(proof.balance, args.auto_claim_to.clone(), verbose)
+                        .await;
+                }
+            }
+
             // Calculate cutoff time
-            let cutoff_time = self.get_cutoff(proof.last_hash_at, args.buffer_time).await;
+            let buffer_time = self.buffer_time(&args);
+            let cutoff_time = self.get_cutoff(proof.last_hash_at, buffer_time).await;
 
             // Build nonce indices
+            let cores = workers.len() as u64;
             let mut nonce_indices = Vec::with_capacity(cores as usize);
             for n in 0..(cores) {
                 let nonce = u64::MAX.saturating_div(cores).saturating_mul(n);
@@ -114,15 +232,28 @@ impl Miner {
             }
 
             // Run drillx
-            let solution = Self::find_hash_par(
-                proof.challenge,
-                cutoff_time,
-                cores,
-                config.min_difficulty as u32,
-                nonce_indices.as_slice(),
-                None,
-            )
-            .await;
+            let job = Job {
+                challenge: proof.challenge,
+                nonces: nonce_indices,
+                deadline: Instant::now() + Duration::from_secs(cutoff_time),
+                min_difficulty: config.min_difficulty as u32,
+                improvements: None,
+                policy: self.stop_policy(&args),
+            };
+            let (solution, stop_reason) = self.find_hash_par(workers, job).await;
+
+            // Discard partial work on shutdown
+            if self.shutdown.is_requested() {
+                break;
+            }
+
+            // Start round record
+            let mut round = JournalEntry::new(RoundKind::Solo);
+            round.challenge = Some(bs58::encode(proof.challenge).into_string());
+            round.cutoff = Some(cutoff_time);
+            round.buffer = Some(buffer_time);
+            round.best_difficulty = Some(solution.to_hash().difficulty() as u64);
+            round.stop_reason = Some(stop_reason);
 
             // Build instruction set
             let mut ixs = vec![ore_api::sdk::auth(proof_pda(signer.pubkey()).0)];
@@ -147,13 +278,25 @@ impl Miner {
             ixs.push(mine_ix);
 
             // Submit transaction
-            match self
-                .send_and_confirm(&ixs, ComputeBudget::Fixed(compute_budget), false)
-                .await
-            {
-                Ok(sig) => self.fetch_solo_mine_event(sig, verbose).await,
+            let tip = *self.tip.read().unwrap();
+            self.metrics
+                .rounds_submitted
+                .fetch_add(1, Ordering::Relaxed);
+            let send_timer = Instant::now();
+            let result = self
+                .send_and_confirm(&ixs, ComputeBudget::Dynamic(compute_budget), false)
+                .await;
+            self.metrics.record_send(send_timer.elapsed());
+            match result {
+                Ok(sig) => {
+                    self.adaptive_buffer.record_send(send_timer.elapsed());
+                    round.tip = tip;
+                    self.fetch_solo_mine_event(sig, round, verbose).await
+                }
                 Err(err) => {
-                    let mining_data = SoloMiningData::failed();
+                    self.record_round(&round.failed(err.to_string()));
+                    let mut mining_data = SoloMiningData::failed();
+                    mining_data.buffer = format!("{}s", buffer_time);
                     let mut data = self.solo_mining_data.write().unwrap();
                     if !data.is_empty() {
                         data.remove(0);
@@ -166,7 +309,9 @@ impl Miner {
 
                     // Log mining table
                     self.update_solo_mining_table(verbose);
-                    println!("{}: {}", "ERROR".bold().red(), err);
+                    if !self.log_format.is_headless() {
+                        println!("{}: {}", "ERROR".bold().red(), err);
+                    }
 
                     return;
                 }
@@ -174,46 +319,101 @@ impl Miner {
         }
     }
 
-    async fn mine_pool(&self, args: MineArgs, pool: &Pool) -> Result<(), Error> {
+    async fn mine_pool(
+        &self,
+        args: MineArgs,
+        pools: &mut PoolSet,
+        workers: &WorkerPool,
+    ) -> Result<(), Error> {
         // Register, if needed
-        let pool_member = pool.post_pool_register(self).await?;
-        let nonce_index = pool_member.id as u64;
+        pools.register(self).await?;
 
         // Get device id
-        let device_id = args.device_id.unwrap_or(0);
+        let mut device_lock = None;
 
         // Get verbose flag
         let verbose = args.verbose;
 
-        // Check num threads
-        let cores = self.parse_cores(args.cores);
-        self.check_num_cores(cores);
-
-        // Init channel for continuous submission
-        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Solution>();
-        tokio::spawn({
-            let miner = self.clone();
-            let pool = pool.clone();
-            async move {
-                while let Some(solution) = rx.recv().await {
-                    if let Err(err) = pool.post_pool_solution(&miner, &solution).await {
-                        println!("error submitting solution: {:?}", err);
-                    }
-                }
-            }
-        });
-
         // Start mining loop
         let mut last_hash_at = 0;
-        loop {
+        let mut pool_unreachable = false;
+        let mut feed = None;
+        while !self.shutdown.is_requested() {
+            // Return to a higher-priority pool once it recovers
+            if pools.fail_back(self).await {
+                last_hash_at = 0;
+                pool_unreachable = false;
+            }
+            let pool = pools.active().clone();
+            let nonce_index = pools.member().id as u64;
+
             // Fetch latest challenge
-            let member_challenge = match pool.get_updated_pool_challenge(self, last_hash_at).await {
-                Err(_err) => {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            let member_challenge = tokio::select! {
+                challenge = tokio::time::timeout(
+                    CHALLENGE_TIMEOUT,
+                    self.next_pool_challenge(&pool, &mut feed, last_hash_at),
+                ) => challenge.unwrap_or_else(|_| {
+                    Err(Error::Internal("timed out waiting for a new challenge".to_string()))
+                }),
+                _ = self.shutdown.wait() => break,
+            };
+            let member_challenge = match member_challenge {
+                Err(err) => {
+                    if !pool_unreachable {
+                        pool_unreachable = true;
+                        self.notifier.notify(
+                            NotifyEvent::PoolUnreachable,
+                            serde_json::json!({
+                                "pool_url": pool.pool_url,
+                                "error": err.to_string(),
+                            }),
+                        );
+                    }
+                    if pools.record_failure(self).await {
+                        last_hash_at = 0;
+                        pool_unreachable = false;
+                    } else {
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
                     continue;
                 }
                 Ok(member_challenge) => member_challenge,
             };
+            pool_unreachable = false;
+
+            // Verify challenge against chain state
+            if let Some(check) = args.verify_challenge {
+                let verified = match Pubkey::from_str(&pools.member().pool_address) {
+                    Ok(pool_address) => {
+                        verify_pool_challenge(&self.rpc_client, pool_address, &member_challenge)
+                            .await
+                    }
+                    Err(_) => Err("invalid pool address".to_string()),
+                };
+                if let Err(err) = verified {
+                    self.notifier.notify(
+                        NotifyEvent::ChallengeMismatch,
+                        serde_json::json!({
+                            "pool_url": pool.pool_url,
+                            "error": err,
+                        }),
+                    );
+                    match check {
+                        ChallengeCheck::Warn => println!(
+                            "{} Unverified challenge from {}: {}",
+                            "WARNING".bold().yellow(),
+                            pool.pool_url,
+                            err
+                        ),
+                        ChallengeCheck::Stop => {
+                            return Err(Error::Internal(format!(
+                                "unverified challenge from {}: {}",
+                                pool.pool_url, err
+                            )))
+                        }
+                    }
+                }
+            }
 
             // Log mining table
             self.update_pool_mining_table(verbose);
@@ -222,7 +422,8 @@ impl Miner {
             last_hash_at = member_challenge.challenge.lash_hash_at;
 
             // Compute cutoff time
-            let cutoff_time = self.get_cutoff(last_hash_at, args.buffer_time).await;
+            let buffer_time = self.buffer_time(&args);
+            let cutoff_time = self.get_cutoff(last_hash_at, buffer_time).await;
 
             // Build nonce indices
             let num_total_members = member_challenge.num_total_members.max(1);
@@ -230,6 +431,27 @@ impl Miner {
             let device_search_space_size =
                 member_search_space_size.saturating_div(member_challenge.num_devices as u64);
 
+            // Claim a device slot, if allocating automatically
+            let device_id = match args.device_id {
+                None => 0,
+                Some(DeviceId::Fixed(device_id)) => device_id,
+                Some(DeviceId::Auto) => {
+                    let lock = match device_lock.as_mut() {
+                        Some(lock) => lock,
+                        None => device_lock.insert(DeviceLock::acquire(
+                            args.device_lock_dir
+                                .as_ref()
+                                .map(PathBuf::from)
+                                .unwrap_or_else(default_device_lock_dir),
+                            self.signer().pubkey(),
+                            member_challenge.num_devices as u64,
+                        )?),
+                    };
+                    lock.heartbeat();
+                    lock.id
+                }
+            };
+
             // Check device id doesn't go beyond pool limit
             if (device_id as u8) > member_challenge.num_devices {
                 return Err(Error::TooManyDevices);
@@ -240,6 +462,7 @@ impl Miner {
                 + device_id.saturating_mul(device_search_space_size);
 
             // Split nonce-device space for muliple cores
+            let cores = workers.len() as u64;
             let range_per_core = device_search_space_size.saturating_div(cores);
             let mut nonce_indices = Vec::with_capacity(cores as usize);
             for n in 0..(cores) {
@@ -247,173 +470,148 @@ impl Miner {
                 nonce_indices.push(index);
             }
 
+            // Contribute improved solutions to the active pool as they are found
+            let deadline = Instant::now() + Duration::from_secs(cutoff_time);
+            let (pipeline, improvements) =
+                SolutionPipeline::spawn(self, &pool, deadline + Duration::from_secs(buffer_time));
+
             // Run drillx
-            let solution = Self::find_hash_par(
-                member_challenge.challenge.challenge,
-                cutoff_time,
-                cores,
-                member_challenge.challenge.min_difficulty as u32,
-                nonce_indices.as_slice(),
-                Some(tx.clone()),
-            )
-            .await;
+            let job = Job {
+                challenge: member_challenge.challenge.challenge,
+                nonces: nonce_indices,
+                deadline,
+                min_difficulty: member_challenge.challenge.min_difficulty as u32,
+                improvements: Some(improvements),
+                policy: self.stop_policy(&args),
+            };
+            let (solution, stop_reason) = self.find_hash_par(workers, job).await;
+
+            // Discard partial work on shutdown
+            if self.shutdown.is_requested() {
+                break;
+            }
+
+            // Start round record
+            let mut round = JournalEntry::new(RoundKind::Pool);
+            round.challenge =
+                Some(bs58::encode(member_challenge.challenge.challenge).into_string());
+            round.cutoff = Some(cutoff_time);
+            round.buffer = Some(buffer_time);
+            round.best_difficulty = Some(solution.to_hash().difficulty() as u64);
+            round.stop_reason = Some(stop_reason);
 
-            // Post solution to pool server
-            match pool.post_pool_solution(self, &solution).await {
-                Err(_err) => {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            // Contribute final solution to pool server
+            self.metrics
+                .rounds_submitted
+                .fetch_add(1, Ordering::Relaxed);
+            round.pool_url = Some(pool.pool_url.clone());
+            let contributions = pipeline.finish(solution).await;
+            round.contributions = contributions.records;
+            match contributions.best_accepted {
+                None => {
+                    let err = contributions
+                        .last_error
+                        .map(|err| err.to_string())
+                        .unwrap_or_else(|| "No solution was accepted".to_string());
+                    self.add_pool_mining_row(PoolMiningData::failed(
+                        format!("Contribution failed: {}", err),
+                        round.acceptance_rate(),
+                        pool.pool_url.clone(),
+                    ));
+                    self.record_round(&round.failed(err));
+                    if pools.record_failure(self).await {
+                        last_hash_at = 0;
+                    } else {
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
                     continue;
                 }
-                Ok(()) => {
-                    self.fetch_pool_mine_event(pool, last_hash_at, verbose)
+                Some(_) => {
+                    pools.record_success();
+                    self.fetch_pool_mine_event(&pool, feed.as_mut(), last_hash_at, round, verbose)
                         .await;
                 }
             }
         }
+        Ok(())
     }
 
-    async fn find_hash_par(
-        challenge: [u8; 32],
-        cutoff_time: u64,
-        cores: u64,
-        min_difficulty: u32,
-        nonce_indices: &[u64],
-        pool_channel: Option<tokio::sync::mpsc::UnboundedSender<Solution>>,
-    ) -> Solution {
-        // Dispatch job to each thread
-        let progress_bar = Arc::new(spinner::new_progress_bar());
-        let global_best_difficulty = Arc::new(RwLock::new(0u32));
-
-        progress_bar.set_message("Mining...");
-        let core_ids = core_affinity::get_core_ids().expect("Failed to fetch core count");
-        let core_ids = core_ids.into_iter().filter(|id| id.id < (cores as usize));
-        let handles: Vec<_> = core_ids
-            .map(|i| {
-                let global_best_difficulty = Arc::clone(&global_best_difficulty);
-                std::thread::spawn({
-                    let progress_bar = progress_bar.clone();
-                    let nonce = nonce_indices[i.id];
-                    let mut memory = equix::SolverMemory::new();
-                    let pool_channel = pool_channel.clone();
-                    move || {
-                        // Pin to core
-                        let _ = core_affinity::set_for_current(i);
-
-                        // Start hashing
-                        let timer = Instant::now();
-                        let mut nonce = nonce;
-                        let mut best_nonce = nonce;
-                        let mut best_difficulty = 0;
-                        let mut best_hash = Hash::default();
-                        loop {
-                            // Get hashes
-                            let hxs = drillx::hashes_with_memory(
-                                &mut memory,
-                                &challenge,
-                                &nonce.to_le_bytes(),
-                            );
-
-                            // Look for best difficulty score in all hashes
-                            for hx in hxs {
-                                let difficulty = hx.difficulty();
-                                if difficulty.gt(&best_difficulty) {
-                                    best_nonce = nonce;
-                                    best_difficulty = difficulty;
-                                    best_hash = hx;
-                                    if best_difficulty.gt(&*global_best_difficulty.read().unwrap())
-                                    {
-                                        // Update best global difficulty
-                                        *global_best_difficulty.write().unwrap() = best_difficulty;
-
-                                        // Continuously upload best solution to pool
-                                        if difficulty.ge(&min_difficulty) {
-                                            if let Some(ref ch) = pool_channel {
-                                                let digest = best_hash.d;
-                                                let nonce = nonce.to_le_bytes();
-                                                let solution = Solution {
-                                                    d: digest,
-                                                    n: nonce,
-                                                };
-                                                if let Err(err) = ch.send(solution) {
-                                                    println!("{} {:?}", "ERROR".bold().red(), err);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-
-                            // Exit if time has elapsed
-                            if nonce % 100 == 0 {
-                                let global_best_difficulty =
-                                    *global_best_difficulty.read().unwrap();
-                                if timer.elapsed().as_secs().ge(&cutoff_time) {
-                                    if i.id == 0 {
-                                        progress_bar.set_message(format!(
-                                            "Mining...\n  Best score: {}",
-                                            global_best_difficulty,
-                                        ));
-                                    }
-                                    if global_best_difficulty.ge(&min_difficulty) {
-                                        // Mine until min difficulty has been met
-                                        break;
-                                    }
-                                } else if i.id == 0 {
-                                    progress_bar.set_message(format!(
-                                        "Mining...\n  Best score: {}\n  Time remaining: {}",
-                                        global_best_difficulty,
-                                        format_duration(
-                                            cutoff_time.saturating_sub(timer.elapsed().as_secs())
-                                                as u32
-                                        ),
-                                    ));
-                                }
-                            }
+    /// Waits for a new challenge from `pool`, pushed over its feed if the server supports
+    /// one and polled otherwise.
+    async fn next_pool_challenge(
+        &self,
+        pool: &Pool,
+        feed: &mut Option<PoolFeed>,
+        last_hash_at: i64,
+    ) -> Result<MemberChallenge, Error> {
+        // Subscribe to the active pool, if needed
+        if feed.as_ref().map_or(true, |feed| {
+            feed.is_closed() || feed.pool_url != pool.pool_url
+        }) {
+            *feed = pool.subscribe(self).await;
+        }
+        if let Some(subscription) = feed.as_mut() {
+            let challenge = tokio::time::timeout(
+                FEED_CHALLENGE_TIMEOUT,
+                subscription.next_challenge(last_hash_at),
+            )
+            .await;
+            if let Ok(Some(challenge)) = challenge {
+                return Ok(challenge);
+            }
 
-                            // Increment nonce
-                            nonce += 1;
-                        }
+            // Fall back to polling a stalled or closed feed
+            *feed = None;
+            pool.defer_feed();
+        }
+        pool.get_updated_pool_challenge(self, last_hash_at).await
+    }
 
-                        // Return the best nonce
-                        (best_nonce, best_difficulty, best_hash)
-                    }
-                })
-            })
-            .collect();
+    async fn find_hash_par(&self, workers: &WorkerPool, job: Job) -> (Solution, StopReason) {
+        // Dispatch job to the worker pool
+        let progress_bar = self.log_format.progress_bar();
 
-        // Join handles and return best nonce
-        let mut best_nonce: u64 = 0;
-        let mut best_difficulty = 0;
-        let mut best_hash = Hash::default();
-        for h in handles {
-            if let Ok((nonce, difficulty, hash)) = h.join() {
-                if difficulty > best_difficulty {
-                    best_difficulty = difficulty;
-                    best_nonce = nonce;
-                    best_hash = hash;
-                }
+        // Wait for workers, stopping early on shutdown
+        let run = workers.run(job, &progress_bar);
+        tokio::pin!(run);
+        let result = tokio::select! {
+            biased;
+            result = &mut run => result,
+            _ = self.shutdown.wait() => {
+                workers.cancel();
+                run.await
             }
-        }
+        };
+        self.metrics
+            .record_hashes(workers.cores(), &result.worker_hashes, result.elapsed);
 
-        Solution::new(best_hash.d, best_nonce.to_le_bytes())
+        (result.solution, result.stop_reason)
     }
 
-    pub fn parse_cores(&self, cores: String) -> u64 {
-        if cores == "ALL" {
-            num_cpus::get() as u64
-        } else {
-            cores.parse::<u64>().unwrap()
-        }
+    fn watchdog(&self, args: &MineArgs) -> Result<Watchdog, Error> {
+        let top_up = match args.top_up_keypair {
+            Some(ref filepath) => {
+                let keypair = read_keypair_file(filepath).map_err(|err| {
+                    Error::Internal(format!(
+                        "failed to read top-up keypair {}: {}",
+                        filepath, err
+                    ))
+                })?;
+                Some(TopUp::new(
+                    keypair,
+                    sol_to_lamports(args.top_up_amount),
+                    sol_to_lamports(args.top_up_daily_limit),
+                ))
+            }
+            None => None,
+        };
+        Ok(Watchdog::new(sol_to_lamports(args.low_balance), top_up))
     }
 
-    pub fn check_num_cores(&self, cores: u64) {
-        let num_cores = num_cpus::get() as u64;
-        if cores.gt(&num_cores) {
-            println!(
-                "{} Cannot exceeds available cores ({})",
-                "WARNING".bold().yellow(),
-                num_cores
-            );
+    fn stop_policy(&self, args: &MineArgs) -> StopPolicy {
+        StopPolicy {
+            target_difficulty: args.target_difficulty,
+            max_duration: args.max_seconds.map(Duration::from_secs),
         }
     }
 
@@ -428,6 +626,14 @@ impl Miner {
             .le(&clock.unix_timestamp)
     }
 
+    fn buffer_time(&self, args: &MineArgs) -> u64 {
+        if args.adaptive_buffer {
+            self.adaptive_buffer.buffer(args.buffer_time)
+        } else {
+            args.buffer_time
+        }
+    }
+
     async fn get_cutoff(&self, last_hash_at: i64, buffer_time: u64) -> u64 {
         let clock = get_clock(&self.rpc_client)
             .await
@@ -439,7 +645,7 @@ impl Miner {
             .max(0) as u64
     }
 
-    async fn find_bus(&self) -> Pubkey {
+    pub async fn find_bus(&self) -> Pubkey {
         // Fetch the bus with the largest balance
         if let Ok(accounts) = self.rpc_client.get_multiple_accounts(&BUS_ADDRESSES).await {
             let mut top_bus_balance: u64 = 0;
@@ -462,9 +668,10 @@ impl Miner {
         BUS_ADDRESSES[i]
     }
 
-    async fn fetch_solo_mine_event(&self, sig: Signature, verbose: bool) {
+    async fn fetch_solo_mine_event(&self, sig: Signature, mut round: JournalEntry, verbose: bool) {
         // Add loading row
-        let mining_data = SoloMiningData::fetching(sig);
+        let mut mining_data = SoloMiningData::fetching(sig);
+        mining_data.buffer = format_buffer(round.buffer);
         let mut data = self.solo_mining_data.write().unwrap();
         data.insert(0, mining_data);
         if !data.is_empty() {
@@ -486,6 +693,7 @@ impl Miner {
             if tx.is_ok() {
                 break;
             }
+            self.metrics.record_rpc_error();
             sleep(Duration::from_secs(1));
             attempts += 1;
             if attempts > 30 {
@@ -493,9 +701,16 @@ impl Miner {
             }
         }
 
+        // Transaction landed, even if its event cannot be parsed
+        round.status = RoundStatus::Confirmed;
+        round.signature = Some(sig.to_string());
+        round.error = Some("Failed to fetch mine event".to_string());
+
         // Parse transaction response
         if let Ok(tx) = tx {
+            round.slot = Some(tx.slot);
             if let Some(meta) = tx.transaction.meta {
+                round.fee = meta.fee;
                 if let OptionSerializer::Some(log_messages) = meta.log_messages {
                     if let Some(return_log) = log_messages
                         .iter()
@@ -505,8 +720,15 @@ impl Miner {
                             return_log.strip_prefix(&format!("Program return: {} ", ore_api::ID))
                         {
                             if let Ok(return_data) = return_data.from_base64() {
-                                let mut data = self.solo_mining_data.write().unwrap();
                                 let event = MineEvent::from_bytes(&return_data);
+                                round.timestamp = tx.block_time.unwrap_or_default();
+                                round.difficulty = event.difficulty;
+                                round.base_reward = event.net_base_reward;
+                                round.boost_reward = event.net_miner_boost_reward;
+                                round.total_reward = event.net_reward;
+                                round.timing = event.timing;
+                                round.error = None;
+                                let mut data = self.solo_mining_data.write().unwrap();
                                 let mining_data = SoloMiningData {
                                     signature: if verbose {
                                         sig.to_string()
@@ -535,6 +757,7 @@ impl Miner {
                                         "0".to_string()
                                     },
                                     timing: format!("{}s", event.timing),
+                                    buffer: format_buffer(round.buffer),
                                     status: "Confirmed".bold().green().to_string(),
                                 };
                                 data.insert(0, mining_data);
@@ -544,69 +767,243 @@ impl Miner {
                 }
             }
         }
+
+        // Record round
+        self.record_round(&round);
     }
 
-    async fn fetch_pool_mine_event(&self, pool: &Pool, last_hash_at: i64, verbose: bool) {
-        let mining_data = match pool
-            .get_latest_pool_event(self.signer().pubkey(), last_hash_at)
-            .await
-        {
-            Ok(event) => PoolMiningData {
-                signature: if verbose {
-                    event.signature.to_string()
-                } else {
-                    format!("{}...", event.signature.to_string()[..8].to_string())
-                },
-                block: event.block.to_string(),
-                timestamp: format_timestamp(event.timestamp as i64),
-                timing: format!("{}s", event.timing),
-                difficulty: event.difficulty.to_string(),
-                base_reward: if event.net_base_reward > 0 {
-                    format!("{:#.11}", amount_u64_to_f64(event.net_base_reward))
-                } else {
-                    "0".to_string()
-                },
-                boost_reward: if event.net_miner_boost_reward > 0 {
-                    format!("{:#.11}", amount_u64_to_f64(event.net_miner_boost_reward))
-                } else {
-                    "0".to_string()
-                },
-                total_reward: if event.net_reward > 0 {
-                    format!("{:#.11}", amount_u64_to_f64(event.net_reward))
-                } else {
-                    "0".to_string()
-                },
-                my_difficulty: event.member_difficulty.to_string(),
-                my_reward: if event.member_reward > 0 {
-                    format!("{:#.11}", amount_u64_to_f64(event.member_reward))
-                } else {
-                    "0".to_string()
-                },
-            },
-            Err(err) => PoolMiningData {
-                signature: format!("Failed to fetch event: {:?}", err),
-                block: "".to_string(),
-                timestamp: "".to_string(),
-                timing: "".to_string(),
-                difficulty: "".to_string(),
-                base_reward: "".to_string(),
-                boost_reward: "".to_string(),
-                total_reward: "".to_string(),
-                my_difficulty: "".to_string(),
-                my_reward: "".to_string(),
-            },
+    async fn fetch_pool_mine_event(
+        &self,
+        pool: &Pool,
+        feed: Option<&mut PoolFeed>,
+        last_hash_at: i64,
+        mut round: JournalEntry,
+        verbose: bool,
+    ) {
+        // Wait for the event to be pushed, falling back to polling
+        let pushed = match feed {
+            Some(feed) => feed.next_event(last_hash_at, EVENT_TIMEOUT).await,
+            None => None,
+        };
+        let event = match pushed {
+            Some(event) => Ok(event),
+            None => pool.get_latest_pool_event(self, last_hash_at).await,
         };
+        let mining_data = match event {
+            Ok(event) => {
+                round.status = RoundStatus::Confirmed;
+                round.timestamp = event.timestamp as i64;
+                round.signature = Some(event.signature.to_string());
+                round.slot = Some(event.block);
+                round.difficulty = event.difficulty;
+                round.base_reward = event.net_base_reward;
+                round.boost_reward = event.net_miner_boost_reward;
+                round.total_reward = event.net_reward;
+                round.timing = event.timing;
+                round.member_difficulty = Some(event.member_difficulty);
+                round.member_reward = Some(event.member_reward);
+                self.record_round(&round);
+                PoolMiningData {
+                    signature: if verbose {
+                        event.signature.to_string()
+                    } else {
+                        format!("{}...", event.signature.to_string()[..8].to_string())
+                    },
+                    block: event.block.to_string(),
+                    timestamp: format_timestamp(event.timestamp as i64),
+                    timing: format!("{}s", event.timing),
+                    buffer: format_buffer(round.buffer),
+                    difficulty: event.difficulty.to_string(),
+                    base_reward: if event.net_base_reward > 0 {
+                        format!("{:#.11}", amount_u64_to_f64(event.net_base_reward))
+                    } else {
+                        "0".to_string()
+                    },
+                    boost_reward: if event.net_miner_boost_reward > 0 {
+                        format!("{:#.11}", amount_u64_to_f64(event.net_miner_boost_reward))
+                    } else {
+                        "0".to_string()
+                    },
+                    total_reward: if event.net_reward > 0 {
+                        format!("{:#.11}", amount_u64_to_f64(event.net_reward))
+                    } else {
+                        "0".to_string()
+                    },
+                    my_difficulty: event.member_difficulty.to_string(),
+                    my_reward: if event.member_reward > 0 {
+                        format!("{:#.11}", amount_u64_to_f64(event.member_reward))
+                    } else {
+                        "0".to_string()
+                    },
+                    accepted: round.acceptance_rate(),
+                    pool: pool.pool_url.clone(),
+                }
+            }
+            Err(err) => {
+                let row = PoolMiningData::failed(
+                    format!("Failed to fetch event: {}", err),
+                    round.acceptance_rate(),
+                    pool.pool_url.clone(),
+                );
+                self.record_round(&round.failed(format!("Failed to fetch event: {}", err)));
+                row
+            }
+        };
+        self.add_pool_mining_row(mining_data);
+    }
 
-        // Add row
+    fn add_pool_mining_row(&self, row: PoolMiningData) {
         let mut data = self.pool_mining_data.write().unwrap();
-        data.insert(0, mining_data);
+        data.insert(0, row);
         if data.len() >= 12 {
             data.pop();
         }
-        drop(data);
+    }
+
+    async fn auto_claim(&self, amount: u64, to: Option<String>, verbose: bool) {
+        match self.send_claim_from_proof(to.clone(), amount).await {
+            Ok(sig) => {
+                self.log_format.log_claim(&sig, amount, to.as_deref());
+                self.notifier.notify(
+                    NotifyEvent::AutoClaim,
+                    serde_json::json!({
+                        "signature": sig.to_string(),
+                        "amount": amount,
+                        "to": to,
+                    }),
+                );
+                let signature = if verbose {
+                    sig.to_string()
+                } else {
+                    format!("{}...", &sig.to_string()[..8])
+                };
+                let mut data = self.solo_mining_data.write().unwrap();
+                data.insert(0, SoloMiningData::claimed(signature, amount));
+                while data.len() > 10 {
+                    data.remove(10);
+                }
+                drop(data);
+                self.update_solo_mining_table(verbose);
+            }
+            Err(err) => {
+                println!(
+                    "{} Failed to claim {} ORE: {}",
+                    "WARNING".bold().yellow(),
+                    amount_u64_to_f64(amount),
+                    err
+                );
+            }
+        }
+    }
+
+    fn record_round(&self, entry: &JournalEntry) {
+        if let (RoundStatus::Confirmed, Some(StopReason::Cutoff), Some(buffer)) =
+            (entry.status, entry.stop_reason, entry.buffer)
+        {
+            self.adaptive_buffer.record_landing(entry.timing, buffer);
+        }
+        self.metrics.record_round(entry);
+        self.log_format.log_round(entry);
+        let event = match entry.status {
+            RoundStatus::Confirmed => NotifyEvent::RoundConfirmed,
+            RoundStatus::Failed => NotifyEvent::RoundFailed,
+        };
+        self.notifier
+            .notify(event, serde_json::to_value(entry).unwrap_or_default());
+        if let Err(err) = self.journal.append(entry) {
+            println!(
+                "{} Failed to write journal at {}: {}",
+                "WARNING".bold().yellow(),
+                self.journal.path.display(),
+                err
+            );
+        }
+    }
+
+    fn print_session_summary(&self, duration: Duration) {
+        let metrics = &self.metrics;
+        let confirmed = metrics.rounds_confirmed.load(Ordering::Relaxed);
+        let failed = metrics.rounds_failed.load(Ordering::Relaxed);
+        let rounds = confirmed + failed;
+        let success_rate = if rounds > 0 {
+            confirmed as f64 / rounds as f64 * 100f64
+        } else {
+            0f64
+        };
+        let earned = metrics.base_reward.load(Ordering::Relaxed)
+            + metrics.boost_reward.load(Ordering::Relaxed)
+            + metrics.pool_reward.load(Ordering::Relaxed);
+        let fees = metrics.fees.load(Ordering::Relaxed);
+        let tips = metrics.tips.load(Ordering::Relaxed);
+
+        // Print as a single record in json mode
+        if self.log_format == LogFormat::Json {
+            let summary = serde_json::json!({
+                "summary": true,
+                "duration": duration.as_secs(),
+                "rounds": rounds,
+                "confirmed": confirmed,
+                "failed": failed,
+                "success_rate": success_rate,
+                "earned": earned,
+                "fees": fees,
+                "tips": tips,
+                "average_difficulty": metrics.average_difficulty(),
+                "hashrate": metrics.effective_hashrate(),
+            });
+            println!("{}", summary);
+            return;
+        }
+
+        let data = vec![
+            TableData {
+                key: "Duration".to_string(),
+                value: format_duration(duration.as_secs() as u32),
+            },
+            TableData {
+                key: "Rounds".to_string(),
+                value: rounds.to_string(),
+            },
+            TableData {
+                key: "Success rate".to_string(),
+                value: format!("{:.1}% ({} failed)", success_rate, failed),
+            },
+            TableData {
+                key: "Earned".to_string(),
+                value: format!("{:#.11} ORE", amount_u64_to_f64(earned))
+                    .bold()
+                    .yellow()
+                    .to_string(),
+            },
+            TableData {
+                key: "Fees".to_string(),
+                value: format!("{} SOL", lamports_to_sol(fees)),
+            },
+            TableData {
+                key: "Tips".to_string(),
+                value: format!("{} SOL", lamports_to_sol(tips)),
+            },
+            TableData {
+                key: "Average score".to_string(),
+                value: format!("{:.2}", metrics.average_difficulty()),
+            },
+            TableData {
+                key: "Hashpower".to_string(),
+                value: format!("{:.0} H/sec", metrics.effective_hashrate()),
+            },
+        ];
+        let mut table = Table::new(data);
+        table.with(Remove::row(Rows::first()));
+        table.modify(Columns::single(1), Alignment::right());
+        table.with(Style::blank());
+        table.section_title(0, "Session");
+        println!("\n{table}\n");
     }
 
     fn update_solo_mining_table(&self, verbose: bool) {
+        if self.log_format.is_headless() {
+            return;
+        }
         execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0)).unwrap();
         let mut rows: Vec<SoloMiningData> = vec![];
         let data = self.solo_mining_data.read().unwrap();
@@ -626,6 +1023,9 @@ impl Miner {
     }
 
     fn update_pool_mining_table(&self, verbose: bool) {
+        if self.log_format.is_headless() {
+            return;
+        }
         execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0)).unwrap();
         let mut rows: Vec<PoolMiningData> = vec![];
         let data = self.pool_mining_data.read().unwrap();
@@ -657,9 +1057,161 @@ impl Miner {
 
         // Submit transaction
         if ixs.len() > 0 {
-            self.send_and_confirm(&ixs, ComputeBudget::Fixed(400_000), false)
+            self.send_and_confirm(&ixs, ComputeBudget::Dynamic(400_000), false)
                 .await
                 .ok();
         }
     }
 }
+
+fn format_buffer(buffer: Option<u64>) -> String {
+    match buffer {
+        Some(buffer) => format!("{}s", buffer),
+        None => "–".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::RwLock;
+
+    use clap::Parser;
+    use ore_api::consts::ONE_MINUTE;
+
+    use super::*;
+
+    /// Builds a miner and a pool against a fresh simulated chain, with the keypair and
+    /// journal in a temporary directory.
+    fn simulated_miner() -> (Miner, SimulatedPool, Pool) {
+        let keypair = Keypair::new();
+        let dir = std::env::temp_dir().join(format!("ore-test-{}", keypair.pubkey()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let keypair_filepath = dir.join("id.json");
+        write_keypair_file(&keypair, &keypair_filepath).unwrap();
+        let keypair_filepath = keypair_filepath.to_string_lossy().to_string();
+
+        let chain = Arc::new(Mutex::new(Chain::new(keypair.pubkey())));
+        let simulated = SimulatedPool::new("simulated".to_string(), chain.clone());
+        let rpc_client = Arc::new(RpcClient::new_sender(
+            SimulatedRpc::new(chain),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        ));
+        let miner = Miner::new(
+            rpc_client.clone(),
+            None,
+            Some(keypair_filepath.clone()),
+            None,
+            false,
+            10,
+            Some(keypair_filepath),
+            rpc_client,
+            Arc::new(RwLock::new(0)),
+            Default::default(),
+            Default::default(),
+            Journal::new(dir.join("journal.jsonl")),
+            LogFormat::Json,
+            Notifier::default(),
+        );
+        let pool = Pool::with_client(Arc::new(simulated.clone()));
+        (miner, simulated, pool)
+    }
+
+    /// Contributes a solution to the current round and lands it. Returns the member's
+    /// off-chain balance.
+    async fn earn(miner: &Miner, simulated: &SimulatedPool, pool: &Pool) -> u64 {
+        let challenge = pool.get_pool_challenge(miner).await.unwrap();
+        let (nonce, hash) = (0u64..)
+            .find_map(|nonce| {
+                drillx::hash(&challenge.challenge.challenge, &nonce.to_le_bytes())
+                    .ok()
+                    .filter(|hash| hash.difficulty() > 0)
+                    .map(|hash| (nonce, hash))
+            })
+            .unwrap();
+        pool.post_pool_solution(miner, &Solution::new(hash.d, nonce.to_le_bytes()))
+            .await
+            .unwrap();
+        simulated.fast_forward(ONE_MINUTE + 5);
+        let member = pool.get_pool_member(miner).await.unwrap();
+        assert!(member.total_balance > 0);
+        member.total_balance as u64
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mines_a_pool_round() {
+        let (miner, simulated, pool) = simulated_miner();
+        let args = MineArgs::parse_from(["mine", "--pool-url", "simulated", "--cores", "1"]);
+        let cores = select_cores(&args.cores, args.skip_smt, args.no_pin).unwrap();
+        let workers = WorkerPool::new(cores);
+        let mut pools = PoolSet::new(vec![pool.clone()]);
+
+        // End the round a few seconds into mining
+        simulated.fast_forward(ONE_MINUTE - 10);
+
+        // Stop once the round is recorded
+        let stop = async {
+            while miner.metrics.rounds_confirmed.load(Ordering::Relaxed)
+                + miner.metrics.rounds_failed.load(Ordering::Relaxed)
+                == 0
+            {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            miner.shutdown.request();
+        };
+        let (result, _) = tokio::join!(miner.mine_pool(args, &mut pools, &workers), stop);
+        result.unwrap();
+
+        // The member is credited with its share of the round
+        let rounds = miner.journal.read().unwrap();
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].status, RoundStatus::Confirmed);
+        let member_reward = rounds[0].member_reward.unwrap();
+        assert!(member_reward > 0);
+        let member = pool.get_pool_member(&miner).await.unwrap();
+        assert_eq!(member.total_balance as u64, member_reward);
+        assert_eq!(
+            miner.metrics.pool_reward.load(Ordering::Relaxed),
+            member_reward
+        );
+    }
+
+    #[tokio::test]
+    async fn commits_a_pool_balance() {
+        let (miner, simulated, pool) = simulated_miner();
+        pool.post_pool_register(&miner).await.unwrap();
+        let earned = earn(&miner, &simulated, &pool).await;
+
+        // Attribute the off-chain balance to the member account
+        let update = pool.post_update_balance(&miner).await.unwrap();
+        assert_eq!(update.balance, earned);
+        let pool_address = pool.get_pool_address().await.unwrap().address;
+        let member = pool
+            .get_pool_member_onchain(&miner, pool_address)
+            .await
+            .unwrap();
+        assert_eq!(member.balance, earned);
+        assert_eq!(member.total_balance, earned);
+    }
+
+    #[tokio::test]
+    async fn claims_from_a_pool() {
+        let (miner, simulated, pool) = simulated_miner();
+        pool.post_pool_register(&miner).await.unwrap();
+        let earned = earn(&miner, &simulated, &pool).await;
+        pool.post_update_balance(&miner).await.unwrap();
+
+        // Claim part of the attributed balance
+        let amount = earned / 2;
+        let pool_address = pool.get_pool_address().await.unwrap().address;
+        miner
+            .send_claim_from_pool(None, amount, pool_address)
+            .await
+            .unwrap();
+        let member = pool
+            .get_pool_member_onchain(&miner, pool_address)
+            .await
+            .unwrap();
+        assert_eq!(member.balance, earned - amount);
+        assert_eq!(member.total_balance, earned);
+    }
+}
@@ -1,11 +1,15 @@
 mod account;
 mod benchmark;
 mod claim;
+mod estimate;
+mod history;
 #[cfg(feature = "admin")]
 mod initialize;
 mod mine;
 mod pool;
 mod program;
+mod proxy;
+mod reconcile;
 mod stake;
 mod transaction;
 mod transfer;
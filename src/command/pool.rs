@@ -1,15 +1,17 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use crossterm::style::Stylize;
 use drillx::Solution;
 use ore_api::state::proof_pda;
 use ore_pool_api::state::member_pda;
 use ore_pool_types::{
-    BalanceUpdate, ContributePayload, Member, MemberChallenge, PoolAddress, RegisterPayload,
+    BalanceUpdate, ContributePayload, Member, MemberChallenge, PoolAddress, PoolMemberMiningEvent,
     UpdateBalancePayload,
 };
 use reqwest::StatusCode;
-use solana_rpc_client::spinner;
 use solana_sdk::{
     compute_budget, pubkey::Pubkey, signature::Signature, signer::Signer, transaction::Transaction,
 };
@@ -22,13 +24,19 @@ use tabled::{
     },
     Table, Tabled,
 };
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
 
 use crate::{
     args::{PoolArgs, PoolCommand, PoolCommitArgs},
     error::Error,
+    pool_client::{pool_http_client, HttpPoolClient, PoolClient, PoolError, PoolFeed},
     utils::{
-        self, amount_u64_to_f64, format_timestamp, get_member, get_pool, get_pools, get_proof,
-        ComputeBudget, TableData, TableSectionTitle,
+        self, amount_f64_to_u64, amount_u64_to_f64, format_timestamp, get_member, get_pool,
+        get_pools, get_proof, ComputeBudget, Contribution, NotifyEvent, PoolProbe, PoolSort,
+        PoolStatus, TableData, TableSectionTitle,
     },
     Miner,
 };
@@ -41,6 +49,10 @@ impl Miner {
                 PoolCommand::Commit(commit_args) => {
                     self.pool_commit(args, commit_args).await.unwrap()
                 }
+                PoolCommand::Proxy(proxy_args) => self.pool_proxy(args, proxy_args).await.unwrap(),
+                PoolCommand::Reconcile(reconcile_args) => {
+                    self.pool_reconcile(args, reconcile_args).await.unwrap()
+                }
             }
         } else {
             if let Some(pool_url) = args.pool_url {
@@ -51,40 +63,111 @@ impl Miner {
         }
     }
 
-    async fn list_pools(&self, _args: PoolArgs) -> Result<(), Error> {
+    async fn list_pools(&self, args: PoolArgs) -> Result<(), Error> {
         let pools = get_pools(&self.rpc_client)
             .await
             .expect("Failed to fetch pool accounts");
-        let mut data = vec![];
+        let mut rows = vec![];
         for (pool_address, pool) in pools {
             let url = String::from_utf8(pool.url.to_vec()).unwrap_or_default();
             let url = url.trim_end_matches('\0');
-            let mut point = PoolTableData {
-                address: pool_address.to_string(),
-                url: url.to_string(),
-                balance: "".to_string(),
-                last_hash_at: "".to_string(),
-                lifetime_hashes: "".to_string(),
-                lifetime_rewards: "".to_string(),
-                members: pool.total_members.to_string(),
+            let mut row = PoolRow {
+                address: pool_address,
+                data: PoolTableData {
+                    address: pool_address.to_string(),
+                    url: url.to_string(),
+                    balance: "".to_string(),
+                    last_hash_at: "".to_string(),
+                    lifetime_hashes: "".to_string(),
+                    lifetime_rewards: "".to_string(),
+                    rewards_per_hash: "".to_string(),
+                    members: pool.total_members.to_string(),
+                    latency: "".to_string(),
+                    status: "".to_string(),
+                },
+                probe: None,
+                balance: 0,
+                members: pool.total_members,
+                rewards_per_hash: 0.0,
             };
 
             // Get proof data
             let proof_address = proof_pda(pool_address).0;
             if let Ok(proof) = get_proof(&self.rpc_client, proof_address).await {
-                point.balance = format!("{} ORE", amount_u64_to_f64(proof.balance));
-                point.last_hash_at = format_timestamp(proof.last_hash_at);
-                point.lifetime_hashes = proof.total_hashes.to_string();
-                point.lifetime_rewards = format!("{} ORE", amount_u64_to_f64(proof.total_rewards));
+                row.balance = proof.balance;
+                if proof.total_hashes > 0 {
+                    row.rewards_per_hash = proof.total_rewards as f64 / proof.total_hashes as f64;
+                }
+                row.data.balance = format!("{} ORE", amount_u64_to_f64(proof.balance));
+                row.data.last_hash_at = format_timestamp(proof.last_hash_at);
+                row.data.lifetime_hashes = proof.total_hashes.to_string();
+                row.data.lifetime_rewards =
+                    format!("{} ORE", amount_u64_to_f64(proof.total_rewards));
+                row.data.rewards_per_hash = format!("{:.4} grains", row.rewards_per_hash);
             }
 
             // Push data
-            data.push(point);
+            rows.push(row);
+        }
+
+        // Contact pool servers concurrently
+        if args.probe {
+            let http_client = pool_http_client();
+            let authority = self.signer().pubkey();
+            let probes =
+                futures::future::join_all(rows.iter().map(|row| {
+                    utils::probe_pool(&http_client, &row.data.url, row.address, authority)
+                }))
+                .await;
+            for (row, probe) in rows.iter_mut().zip(probes) {
+                row.data.latency = probe.latency().map_or("–".to_string(), |latency| {
+                    format!("{} ms", latency.as_millis())
+                });
+                row.data.status = match probe.status {
+                    PoolStatus::Ok => "OK".green().bold().to_string(),
+                    PoolStatus::NoUrl => "No url".dark_grey().to_string(),
+                    PoolStatus::Unreachable => "Unreachable".red().bold().to_string(),
+                    PoolStatus::AddressMismatch(address) => {
+                        format!("{} ({})", "Address mismatch".red().bold(), address)
+                    }
+                    PoolStatus::NoChallenge => "No challenge".yellow().bold().to_string(),
+                };
+                row.probe = Some(probe);
+            }
+        }
+
+        // Rank pools, healthy servers first when probed
+        if let Some(sort) = args.sort {
+            rows.sort_by(|a, b| {
+                let healthy = |row: &PoolRow| {
+                    row.probe
+                        .as_ref()
+                        .map_or(true, |probe| probe.status == PoolStatus::Ok)
+                };
+                healthy(b).cmp(&healthy(a)).then_with(|| match sort {
+                    PoolSort::Latency => {
+                        let latency = |row: &PoolRow| {
+                            row.probe
+                                .as_ref()
+                                .and_then(|probe| probe.latency())
+                                .unwrap_or(Duration::MAX)
+                        };
+                        latency(a).cmp(&latency(b))
+                    }
+                    PoolSort::RewardsPerHash => b.rewards_per_hash.total_cmp(&a.rewards_per_hash),
+                    PoolSort::Members => b.members.cmp(&a.members),
+                    PoolSort::Balance => b.balance.cmp(&a.balance),
+                })
+            });
         }
 
         // Print table
+        let data: Vec<PoolTableData> = rows.into_iter().map(|row| row.data).collect();
         let mut table = Table::new(&data);
         table.with(Style::blank());
+        if !args.probe {
+            table.with(Remove::column(Columns::new(8..)));
+        }
         table.modify(Columns::new(1..), Alignment::right());
         table.modify(Rows::first(), Color::BOLD);
         table.with(
@@ -98,10 +181,7 @@ impl Miner {
 
     async fn get_pool(&self, pool_url: String) -> Result<(), Error> {
         // build pool client
-        let pool = Pool {
-            http_client: reqwest::Client::new(),
-            pool_url: pool_url.clone(),
-        };
+        let pool = Pool::new(pool_url.clone());
 
         // Fetch pool account
         let pool_address = pool.get_pool_address().await?.address;
@@ -175,7 +255,8 @@ impl Miner {
             });
             // Get offchain data from pool server
             if let Ok(member_offchain) = pool.get_pool_member(&self).await {
-                let pending_rewards = (member_offchain.total_balance as u64) - member.total_balance;
+                let pending_rewards = (member_offchain.total_balance.max(0) as u64)
+                    .saturating_sub(member.total_balance);
                 data.push(TableData {
                     key: "Pending rewards".to_string(),
                     value: format!("{} ORE", utils::amount_u64_to_string(pending_rewards)),
@@ -199,33 +280,135 @@ impl Miner {
         }
         println!("\n{table}\n");
         if member.is_ok() {
-            println!("Pool operators automatically commit pending rewards to the blockchain at regular intervals. To manually commit your pending rewards now, run the following command:\n\n`ore pool {} commit`\n", pool_url);
+            println!("Pool operators automatically commit pending rewards to the blockchain at regular intervals. To manually commit your pending rewards now, run the following command:\n\n`ore pool {} commit`\n\nTo commit on your own schedule instead, add `--every 6h --min-pending 0.5`.\n", pool_url);
         }
         Ok(())
     }
 
-    async fn pool_commit(&self, args: PoolArgs, _commit_args: PoolCommitArgs) -> Result<(), Error> {
-        let pool = Pool {
-            http_client: reqwest::Client::new(),
-            pool_url: args.pool_url.expect("Pool URL is required"),
+    async fn pool_commit(&self, args: PoolArgs, commit_args: PoolCommitArgs) -> Result<(), Error> {
+        let pool = Pool::new(args.pool_url.expect("Pool URL is required"));
+        let min_pending = amount_f64_to_u64(commit_args.min_pending);
+        let Some(every) = commit_args.every else {
+            return self.pool_commit_once(&pool, min_pending).await;
         };
-        if let Err(err) = pool.post_update_balance(self).await {
-            println!("{:?}", err);
+
+        // Commit on an interval
+        println!(
+            "{} Committing pending balance with {} every {}s once it reaches {} ORE",
+            "INFO".bold().cyan(),
+            pool.pool_url,
+            every.as_secs(),
+            commit_args.min_pending
+        );
+        loop {
+            if let Err(err) = self.pool_commit_once(&pool, min_pending).await {
+                println!(
+                    "{} Failed to commit pending balance: {}",
+                    "WARNING".bold().yellow(),
+                    err
+                );
+            }
+            tokio::time::sleep(every).await;
+        }
+    }
+
+    /// Commits the member's balance if the amount pending attribution on-chain has
+    /// reached `min_pending`.
+    async fn pool_commit_once(&self, pool: &Pool, min_pending: u64) -> Result<(), Error> {
+        // Compare off-chain and on-chain balances
+        let pool_address = pool.get_pool_address().await?.address;
+        let offchain = pool.get_pool_member(self).await?;
+        let onchain = pool.get_pool_member_onchain(self, pool_address).await?;
+        let pending = (offchain.total_balance.max(0) as u64).saturating_sub(onchain.total_balance);
+        if pending == 0 || pending < min_pending {
+            if !self.log_format.is_headless() {
+                println!(
+                    "{} ORE pending. Nothing to commit",
+                    amount_u64_to_f64(pending)
+                );
+            }
+            return Ok(());
+        }
+
+        // Submit attribution
+        let update = pool.post_update_balance(self).await?;
+        self.log_format.log_commit(&pool.pool_url, pending, &update);
+        self.notifier.notify(
+            NotifyEvent::PoolCommit,
+            serde_json::json!({
+                "pool_url": pool.pool_url,
+                "pending": pending,
+                "balance": update.balance,
+                "signature": update.signature.to_string(),
+            }),
+        );
+        if !self.log_format.is_headless() {
+            println!(
+                "Committed {} ORE. Attributed balance is now {} ORE\n{}",
+                amount_u64_to_f64(pending),
+                amount_u64_to_f64(update.balance),
+                update.signature
+            );
         }
         Ok(())
     }
 }
 
+/// A pool server and the member-side logic of its protocol: signing, on-chain accounts
+/// and retries. Requests go through a [`PoolClient`], so the server can be swapped for an
+/// in-memory one.
 #[derive(Clone)]
 pub struct Pool {
-    pub http_client: reqwest::Client,
+    pub client: Arc<dyn PoolClient>,
     pub pool_url: String,
+    feed_retry_at: Arc<Mutex<Option<Instant>>>,
 }
 
 impl Pool {
+    /// Connects to the pool server at `pool_url` over HTTP.
+    pub fn new(pool_url: String) -> Self {
+        Self::with_client(Arc::new(HttpPoolClient::new(pool_url)))
+    }
+
+    pub fn with_client(client: Arc<dyn PoolClient>) -> Self {
+        Self {
+            pool_url: client.url().to_string(),
+            client,
+            feed_retry_at: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Subscribes to challenges and events pushed by the server. Returns None if the server
+    /// does not push them, or did not recently, in which case callers should poll instead.
+    pub async fn subscribe(&self, miner: &Miner) -> Option<PoolFeed> {
+        // Most servers do not push updates, so only ask again after a while
+        if self
+            .feed_retry_at
+            .lock()
+            .unwrap()
+            .is_some_and(|retry_at| Instant::now() < retry_at)
+        {
+            return None;
+        }
+        let feed = self.client.subscribe(miner.signer().pubkey()).await;
+        if feed.is_none() {
+            self.defer_feed();
+        }
+        feed
+    }
+
+    /// Polls the server instead of subscribing to it for a while, after its feed failed.
+    pub fn defer_feed(&self) {
+        *self.feed_retry_at.lock().unwrap() = Some(Instant::now() + FEED_RETRY_INTERVAL);
+    }
+
     pub async fn post_pool_register(&self, miner: &Miner) -> Result<Member, Error> {
+        // submit idempotent register payload
+        // will simply return off-chain account if already registered
         let pubkey = miner.signer().pubkey();
-        let post_url = format!("{}/register", self.pool_url());
+        if let Ok(member) = self.client.register(pubkey).await {
+            return Ok(member);
+        }
         // check if on-chain member account exists already
         let pool_pda = self.get_pool_address().await?;
         if let Err(_err) = self.get_pool_member_onchain(miner, pool_pda.address).await {
@@ -233,40 +416,14 @@ impl Pool {
             // create one before submitting register payload to pool
             let ix = ore_pool_api::sdk::join(pubkey, pool_pda.address, pubkey);
             let _ = miner
-                .send_and_confirm(&[ix], ComputeBudget::Fixed(200_000), false)
+                .send_and_confirm(&[ix], ComputeBudget::Dynamic(200_000), false)
                 .await?;
         };
-        // submit idempotent register payload
-        // will simply return off-chain account if already registered
-        let body = RegisterPayload { authority: pubkey };
-        let resp = self.http_client.post(post_url).json(&body).send().await?;
-        match resp.error_for_status() {
-            Err(err) => {
-                println!("{:?}", err);
-                Err(err).map_err(From::from)
-            }
-            Ok(resp) => resp.json::<Member>().await.map_err(From::from),
-        }
+        Ok(self.client.register(pubkey).await?)
     }
 
     pub async fn get_pool_address(&self) -> Result<PoolAddress, Error> {
-        let get_url = format!("{}/address", self.pool_url());
-        let resp = self.http_client.get(get_url).send().await?;
-        match resp.error_for_status() {
-            Err(_err) => {
-                // Backwards compatibility
-                let get_url = format!("{}/pool-address", self.pool_url());
-                let resp = self.http_client.get(get_url).send().await?;
-                match resp.error_for_status() {
-                    Err(err) => {
-                        println!("{:?}", err);
-                        Err(err).map_err(From::from)
-                    }
-                    Ok(resp) => resp.json::<PoolAddress>().await.map_err(From::from),
-                }
-            }
-            Ok(resp) => resp.json::<PoolAddress>().await.map_err(From::from),
-        }
+        Ok(self.client.address().await?)
     }
 
     pub async fn get_pool_member_onchain(
@@ -282,16 +439,7 @@ impl Pool {
     }
 
     pub async fn get_pool_member(&self, miner: &Miner) -> Result<Member, Error> {
-        let pubkey = miner.signer().pubkey();
-        let get_url = format!("{}/member/{}", self.pool_url(), pubkey);
-        let resp = self.http_client.get(get_url).send().await?;
-        match resp.error_for_status() {
-            Err(err) => {
-                println!("{:?}", err);
-                Err(err).map_err(From::from)
-            }
-            Ok(resp) => resp.json::<Member>().await.map_err(From::from),
-        }
+        Ok(self.client.member(miner.signer().pubkey()).await?)
     }
 
     pub async fn _get_staker_onchain(
@@ -313,7 +461,7 @@ impl Pool {
         last_hash_at: i64,
     ) -> Result<MemberChallenge, Error> {
         let mut retries = 0;
-        let progress_bar = Arc::new(spinner::new_progress_bar());
+        let progress_bar = Arc::new(miner.log_format.progress_bar());
         loop {
             progress_bar.set_message(format!("Fetching new challenge... (retry {})", retries));
             let challenge = self.get_pool_challenge(miner).await?;
@@ -329,38 +477,32 @@ impl Pool {
 
     pub async fn get_latest_pool_event(
         &self,
-        authority: Pubkey,
+        miner: &Miner,
         last_hash_at: i64,
-    ) -> Result<ore_pool_types::PoolMemberMiningEvent, Error> {
-        let get_url = format!("{}/event/latest/{}", self.pool_url(), authority);
+    ) -> Result<PoolMemberMiningEvent, Error> {
+        let authority = miner.signer().pubkey();
         let mut attempts = 0;
-        let progress_bar = Arc::new(spinner::new_progress_bar());
+        let progress_bar = Arc::new(miner.log_format.progress_bar());
         progress_bar.set_message(format!("Fetching mining event... (retry {})", attempts));
         loop {
             // Parse pool event
-            let resp = self.http_client.get(get_url.clone()).send().await?;
-            match resp.error_for_status() {
-                Err(err) => {
-                    if let Some(status) = err.status() {
-                        match status {
-                            StatusCode::NOT_FOUND | StatusCode::BAD_GATEWAY => {
-                                // No op. Retry.
-                            }
-                            _ => {
-                                progress_bar.finish_and_clear();
-                                return Err(Error::Internal(status.to_string()))
-                                    .map_err(From::from);
-                            }
-                        }
+            match self.client.latest_event(authority).await {
+                Ok(event) => {
+                    if event.last_hash_at as i64 >= last_hash_at {
+                        progress_bar.finish_and_clear();
+                        return Ok(event);
                     }
                 }
-                Ok(resp) => {
-                    if let Ok(event) = resp.json::<ore_pool_types::PoolMemberMiningEvent>().await {
-                        if event.last_hash_at as i64 >= last_hash_at {
-                            progress_bar.finish_and_clear();
-                            return Ok(event);
-                        }
-                    }
+                Err(PoolError::Status {
+                    status: StatusCode::NOT_FOUND | StatusCode::BAD_GATEWAY,
+                    ..
+                })
+                | Err(PoolError::InvalidResponse(_)) => {
+                    // No op. Retry.
+                }
+                Err(err) => {
+                    progress_bar.finish_and_clear();
+                    return Err(err.into());
                 }
             }
 
@@ -368,15 +510,20 @@ impl Pool {
             attempts += 1;
             if attempts > 10 {
                 progress_bar.finish_with_message("Retry limit exceeded");
-                return Err(Error::Internal("Retry limit exceeded".to_string()))
-                    .map_err(From::from);
+                return Err(Error::Internal("Retry limit exceeded".to_string()));
             }
             progress_bar.set_message(format!("Fetching mining event... (retry {})", attempts));
             tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         }
     }
 
-    pub async fn post_update_balance(&self, miner: &Miner) -> Result<(), Error> {
+    /// Fetches the latest mining event without waiting for a particular round.
+    pub async fn get_pool_event(&self, miner: &Miner) -> Result<PoolMemberMiningEvent, Error> {
+        Ok(self.client.latest_event(miner.signer().pubkey()).await?)
+    }
+
+    /// Asks the operator to attribute the member's pending balance on-chain.
+    pub async fn post_update_balance(&self, miner: &Miner) -> Result<BalanceUpdate, Error> {
         let signer = &miner.signer();
         let signer_pubkey = &signer.pubkey();
 
@@ -414,54 +561,11 @@ impl Pool {
         };
 
         // post
-        let post_url = format!("{}/commit", self.pool_url());
-        let resp = self
-            .http_client
-            .post(post_url)
-            .json(&paylaod)
-            .send()
-            .await?;
-        match resp.error_for_status() {
-            Err(_err) => {
-                // Backwards compatibility
-                let post_url = format!("{}/update-balance", self.pool_url());
-                let resp = self
-                    .http_client
-                    .post(post_url)
-                    .json(&paylaod)
-                    .send()
-                    .await?;
-                match resp.error_for_status() {
-                    Err(err) => {
-                        println!("{:?}", err);
-                        Err(err).map_err(From::from)
-                    }
-                    Ok(resp) => {
-                        let balance_update = resp.json::<BalanceUpdate>().await;
-                        println!("{:?}", balance_update);
-                        Ok(())
-                    }
-                }
-            }
-            Ok(resp) => {
-                let balance_update = resp.json::<BalanceUpdate>().await;
-                println!("{:?}", balance_update);
-                Ok(())
-            }
-        }
+        Ok(self.client.commit(&paylaod).await?)
     }
 
-    async fn get_pool_challenge(&self, miner: &Miner) -> Result<MemberChallenge, Error> {
-        let pubkey = miner.signer().pubkey();
-        let get_url = format!("{}/challenge/{}", self.pool_url(), pubkey);
-        let resp = self.http_client.get(get_url).send().await?;
-        match resp.error_for_status() {
-            Err(err) => {
-                println!("{:?}", err);
-                Err(err).map_err(From::from)
-            }
-            Ok(resp) => resp.json::<MemberChallenge>().await.map_err(From::from),
-        }
+    pub async fn get_pool_challenge(&self, miner: &Miner) -> Result<MemberChallenge, Error> {
+        Ok(self.client.challenge(miner.signer().pubkey()).await?)
     }
 
     pub async fn post_pool_solution(
@@ -476,30 +580,285 @@ impl Pool {
             solution: *solution,
             signature,
         };
-        let post_url = format!("{}/contribute", self.pool_url());
-        let resp = self
-            .http_client
-            .post(post_url)
-            .json(&payload)
-            .send()
-            .await?;
-        match resp.error_for_status() {
-            Err(err) => {
-                println!("{:?}", err);
-                Err(err).map_err(From::from)
+        Ok(self.client.contribute(&payload).await?)
+    }
+
+    fn sign_solution(miner: &Miner, solution: &Solution) -> Signature {
+        let keypair = &miner.signer();
+        keypair.sign_message(solution.to_bytes().as_slice())
+    }
+}
+
+/// How long to wait for further improvements before contributing the best of a burst.
+const COALESCE_WINDOW: Duration = Duration::from_millis(250);
+
+/// Delay before the first retry of a failed contribution. Doubles after each attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Upper bound on the delay between retries of a failed contribution.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(4);
+
+/// Contributes a round's solutions to a pool in the background. Only solutions that beat
+/// the last accepted difficulty are sent, bursts of improvements are coalesced into their
+/// best solution, and transient failures are retried with backoff until the deadline.
+pub struct SolutionPipeline {
+    finish: oneshot::Sender<Solution>,
+    task: JoinHandle<Contributions>,
+}
+
+/// The solutions contributed to a pool during a round.
+#[derive(Default)]
+pub struct Contributions {
+    pub records: Vec<Contribution>,
+    pub best_accepted: Option<u32>,
+    pub last_error: Option<Error>,
+}
+
+impl SolutionPipeline {
+    /// Starts contributing solutions sent on the returned channel to `pool`.
+    pub fn spawn(
+        miner: &Miner,
+        pool: &Pool,
+        deadline: Instant,
+    ) -> (Self, mpsc::UnboundedSender<Solution>) {
+        let (tx, mut improvements) = mpsc::unbounded_channel::<Solution>();
+        let (finish, mut final_solution) = oneshot::channel::<Solution>();
+        let miner = miner.clone();
+        let pool = pool.clone();
+        let task = tokio::spawn(async move {
+            let mut contributions = Contributions::default();
+            loop {
+                // Wait for an improvement or the round's final solution
+                let (mut solution, last) = tokio::select! {
+                    solution = improvements.recv(), if !improvements.is_closed() => match solution {
+                        Some(solution) => (solution, false),
+                        None => continue,
+                    },
+                    solution = &mut final_solution => match solution {
+                        Ok(solution) => (solution, true),
+                        Err(_) => break,
+                    },
+                };
+
+                // Coalesce a burst of improvements into the best of them
+                if !last {
+                    tokio::time::sleep(COALESCE_WINDOW).await;
+                }
+                while let Ok(next) = improvements.try_recv() {
+                    if next.to_hash().difficulty() > solution.to_hash().difficulty() {
+                        solution = next;
+                    }
+                }
+
+                // Contribute solution
+                contributions
+                    .contribute(&miner, &pool, &solution, deadline)
+                    .await;
+                if last {
+                    break;
+                }
+            }
+            contributions
+        });
+        (Self { finish, task }, tx)
+    }
+
+    /// Contributes the round's final solution, if it is an improvement, and waits for
+    /// outstanding contributions to complete.
+    pub async fn finish(self, solution: Solution) -> Contributions {
+        let _ = self.finish.send(solution);
+        self.task.await.unwrap_or_default()
+    }
+}
+
+impl Contributions {
+    async fn contribute(
+        &mut self,
+        miner: &Miner,
+        pool: &Pool,
+        solution: &Solution,
+        deadline: Instant,
+    ) {
+        // Skip stale and duplicate solutions
+        let difficulty = solution.to_hash().difficulty();
+        if self.best_accepted.is_some_and(|best| difficulty <= best) {
+            return;
+        }
+
+        // Post solution, retrying transient failures
+        let mut attempts = 0;
+        let mut backoff = RETRY_BACKOFF;
+        let result = loop {
+            attempts += 1;
+            match pool.post_pool_solution(miner, solution).await {
+                Err(err) if is_transient(&err) && Instant::now() + backoff < deadline => {
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                }
+                result => break result,
+            }
+        };
+
+        // Record outcome
+        miner.metrics.record_contribution(result.is_ok());
+        self.records.push(Contribution {
+            difficulty,
+            attempts,
+            accepted: result.is_ok(),
+            error: result.as_ref().err().map(|err| err.to_string()),
+        });
+        match result {
+            Ok(()) => self.best_accepted = Some(difficulty),
+            Err(err) => self.last_error = Some(err),
+        }
+    }
+}
+
+/// Whether a failed request may succeed if retried.
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Pool(err) => err.is_transient(),
+        _ => false,
+    }
+}
+
+/// Consecutive failures before mining moves away from the active pool.
+const MAX_POOL_FAILURES: usize = 3;
+
+/// How long to poll a pool server before subscribing to it again, after its feed failed.
+const FEED_RETRY_INTERVAL: Duration = Duration::from_secs(600);
+
+/// How long after failing over to first check whether a higher-priority pool recovered.
+const FAIL_BACK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The longest to wait between recovery checks of higher-priority pools.
+const MAX_FAIL_BACK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Pools to mine with, in order of priority. Mining fails over to the next pool when the
+/// active one stops serving challenges or rejects solutions, and fails back once a
+/// higher-priority pool serves challenges again.
+pub struct PoolSet {
+    pools: Vec<Pool>,
+    members: Vec<Option<Member>>,
+    active: usize,
+    failures: usize,
+    fail_back_at: Instant,
+    fail_back_interval: Duration,
+}
+
+impl PoolSet {
+    pub fn new(pools: Vec<Pool>) -> Self {
+        Self {
+            members: vec![None; pools.len()],
+            pools,
+            active: 0,
+            failures: 0,
+            fail_back_at: Instant::now(),
+            fail_back_interval: FAIL_BACK_INTERVAL,
+        }
+    }
+
+    /// Registers with every pool and activates the highest-priority one that accepted.
+    pub async fn register(&mut self, miner: &Miner) -> Result<(), Error> {
+        let mut first_err = None;
+        for i in 0..self.pools.len() {
+            if let Err(err) = self.register_pool(miner, i).await {
+                println!(
+                    "{} Failed to register with {}: {}",
+                    "WARNING".bold().yellow(),
+                    self.pools[i].pool_url,
+                    err
+                );
+                first_err.get_or_insert(err);
             }
-            Ok(_) => Ok(()),
+        }
+        match self.members.iter().position(Option::is_some) {
+            Some(i) => {
+                self.active = i;
+                Ok(())
+            }
+            None => Err(first_err.unwrap_or(Error::Internal("no pool url".to_string()))),
         }
     }
 
-    fn pool_url(&self) -> String {
-        let pool_url = self.pool_url.clone();
-        pool_url.trim_end_matches('/').to_string()
+    pub fn active(&self) -> &Pool {
+        &self.pools[self.active]
     }
 
-    fn sign_solution(miner: &Miner, solution: &Solution) -> Signature {
-        let keypair = &miner.signer();
-        keypair.sign_message(solution.to_bytes().as_slice())
+    /// The member record of the active pool.
+    pub fn member(&self) -> &Member {
+        self.members[self.active]
+            .as_ref()
+            .expect("Active pool is registered")
+    }
+
+    pub fn record_success(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Counts a failure against the active pool and fails over once there are too many in a
+    /// row. Returns true if the active pool changed.
+    pub async fn record_failure(&mut self, miner: &Miner) -> bool {
+        self.failures += 1;
+        if self.failures < MAX_POOL_FAILURES || self.pools.len() < 2 {
+            return false;
+        }
+        for offset in 1..self.pools.len() {
+            let i = (self.active + offset) % self.pools.len();
+            if self.register_pool(miner, i).await.is_ok() {
+                println!(
+                    "{} {} failed {} times in a row. Failing over to {}",
+                    "WARNING".bold().yellow(),
+                    self.active().pool_url,
+                    self.failures,
+                    self.pools[i].pool_url
+                );
+                self.activate(i);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Switches back to the highest-priority pool that serves challenges again. Checks are
+    /// spaced out, backing off while the higher-priority pools stay down. Returns true if the
+    /// active pool changed.
+    pub async fn fail_back(&mut self, miner: &Miner) -> bool {
+        if self.active == 0 || Instant::now() < self.fail_back_at {
+            return false;
+        }
+        self.fail_back_at = Instant::now() + self.fail_back_interval;
+        self.fail_back_interval = (self.fail_back_interval * 2).min(MAX_FAIL_BACK_INTERVAL);
+        for i in 0..self.active {
+            if self.register_pool(miner, i).await.is_err() {
+                continue;
+            }
+            if self.pools[i].get_pool_challenge(miner).await.is_ok() {
+                println!(
+                    "{} {} recovered. Failing back from {}",
+                    "INFO".bold().cyan(),
+                    self.pools[i].pool_url,
+                    self.active().pool_url
+                );
+                self.activate(i);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn activate(&mut self, i: usize) {
+        self.active = i;
+        self.failures = 0;
+        self.fail_back_at = Instant::now() + FAIL_BACK_INTERVAL;
+        self.fail_back_interval = FAIL_BACK_INTERVAL;
+    }
+
+    async fn register_pool(&mut self, miner: &Miner, i: usize) -> Result<(), Error> {
+        if self.members[i].is_none() {
+            self.members[i] = Some(self.pools[i].post_pool_register(miner).await?);
+        }
+        Ok(())
     }
 }
 
@@ -537,8 +896,24 @@ pub struct PoolTableData {
     lifetime_hashes: String,
     #[tabled(rename = "Lifetime rewards")]
     lifetime_rewards: String,
+    #[tabled(rename = "Rewards per hash")]
+    rewards_per_hash: String,
     #[tabled(rename = "Members")]
     members: String,
     #[tabled(rename = "Url")]
     url: String,
+    #[tabled(rename = "Latency")]
+    latency: String,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+/// A listed pool with the raw values it is ranked by.
+struct PoolRow {
+    address: Pubkey,
+    data: PoolTableData,
+    probe: Option<PoolProbe>,
+    balance: u64,
+    members: u64,
+    rewards_per_hash: f64,
 }
@@ -1,7 +1,7 @@
 use ore_api::consts::{EPOCH_DURATION, BUS_ADDRESSES, TREASURY_TOKENS_ADDRESS, TREASURY_ADDRESS};
 use tabled::{Table, settings::{Style, object::{Rows, Columns}, Alignment, Remove}};
 
-use crate::{utils::{get_config, amount_u64_to_f64, format_timestamp, get_bus, TableData, TableSectionTitle}, Miner};
+use crate::{utils::{get_config, amount_u64_to_f64, format_timestamp, get_bus, reward_rates, TableData, TableSectionTitle}, Miner};
 
 
 impl Miner {
@@ -57,16 +57,12 @@ impl Miner {
 
     async fn fetch_rewards_data(&self, data: &mut Vec<TableData>) {
         let config = get_config(&self.rpc_client).await;
-        for i in 0..32 {
-            let reward_rate = config.base_reward_rate.saturating_mul(2u64.saturating_pow(i));
-            let amount = amount_u64_to_f64(reward_rate).min(1.0);
+        for (difficulty, reward) in reward_rates(&config) {
+            let amount = amount_u64_to_f64(reward);
             data.push(TableData {
-                key: format!("{}{}", config.min_difficulty as u32 + i, if amount >= 1.0 { "+" } else { "" }),
+                key: format!("{}{}", difficulty, if amount >= 1.0 { "+" } else { "" }),
                 value: format!("{:#.11} ORE", amount),
             });
-            if amount >= 1.0 {
-                break;
-            }
         }
     }
 
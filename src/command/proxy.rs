@@ -0,0 +1,449 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use colored::*;
+use drillx::Solution;
+use ore_pool_types::{
+    ContributePayload, Member, MemberChallenge, PoolAddress, PoolMemberMiningEvent, RegisterPayload,
+};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use tabled::{
+    settings::{
+        object::{Columns, Rows},
+        style::BorderColor,
+        Alignment, Border, Color, Highlight, Style,
+    },
+    Table, Tabled,
+};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{
+    args::{PoolArgs, PoolProxyArgs},
+    error::Error,
+    utils::{amount_u64_to_f64, json, read_request, respond},
+    Miner,
+};
+
+use super::pool::Pool;
+
+/// How often the proxy polls the upstream pool for new challenges and events.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many rounds may await their upstream event before the oldest is dropped.
+const MAX_UNSETTLED_ROUNDS: usize = 16;
+
+/// A LAN worker mining through the proxy.
+struct Worker {
+    slot: u64,
+    best_difficulty: u32,
+    contributions: u64,
+    last_difficulty: u32,
+    last_reward: u64,
+    total_reward: u64,
+}
+
+/// The proxy's view of the upstream member and the workers mining for it.
+struct ProxyState {
+    pool_address: Pubkey,
+    pool_bump: u8,
+    member: Member,
+    max_workers: u64,
+    workers: HashMap<Pubkey, Worker>,
+    challenge: Option<MemberChallenge>,
+    best_forwarded: u32,
+    unsettled: VecDeque<(i64, Vec<(Pubkey, u32)>)>,
+    events: HashMap<Pubkey, PoolMemberMiningEvent>,
+}
+
+impl ProxyState {
+    fn register(&mut self, authority: Pubkey) -> Option<Member> {
+        if !self.workers.contains_key(&authority) {
+            let slot = self.workers.len() as u64;
+            if slot >= self.max_workers {
+                return None;
+            }
+            self.workers.insert(
+                authority,
+                Worker {
+                    slot,
+                    best_difficulty: 0,
+                    contributions: 0,
+                    last_difficulty: 0,
+                    last_reward: 0,
+                    total_reward: 0,
+                },
+            );
+        }
+        let worker = self.workers.get(&authority)?;
+        Some(Member {
+            address: self.member.address.clone(),
+            id: self.member.id * self.max_workers as i64 + worker.slot as i64,
+            authority: authority.to_string(),
+            pool_address: self.member.pool_address.clone(),
+            total_balance: worker.total_reward as i64,
+            is_approved: true,
+            is_kyc: self.member.is_kyc,
+            is_synced: true,
+        })
+    }
+
+    /// The upstream challenge, with the member's nonce range split into one range per
+    /// worker slot.
+    fn worker_challenge(&self) -> Option<MemberChallenge> {
+        let mut challenge = self.challenge?;
+        challenge.num_total_members = challenge
+            .num_total_members
+            .max(1)
+            .saturating_mul(self.max_workers);
+        Some(challenge)
+    }
+
+    /// Records a worker's solution. If it beats every solution forwarded upstream this
+    /// round, returns the previous best so it can be restored should forwarding fail.
+    fn contribute(
+        &mut self,
+        authority: &Pubkey,
+        solution: &Solution,
+    ) -> Result<Option<u32>, String> {
+        let challenge = self.challenge.ok_or("no active challenge")?;
+        let Some(worker) = self.workers.get_mut(authority) else {
+            return Err("worker is not registered".to_string());
+        };
+        if !solution.is_valid(&challenge.challenge.challenge) {
+            return Err("invalid solution".to_string());
+        }
+        let difficulty = solution.to_hash().difficulty();
+        if (difficulty as u64) < challenge.challenge.min_difficulty {
+            return Err("difficulty below minimum".to_string());
+        }
+        worker.best_difficulty = worker.best_difficulty.max(difficulty);
+        worker.contributions += 1;
+        if difficulty <= self.best_forwarded {
+            return Ok(None);
+        }
+        Ok(Some(std::mem::replace(
+            &mut self.best_forwarded,
+            difficulty,
+        )))
+    }
+
+    /// Starts a new round, holding the previous round's scores until its event arrives.
+    fn update_challenge(&mut self, challenge: MemberChallenge) {
+        let last_hash_at = challenge.challenge.lash_hash_at;
+        let previous = self.challenge.replace(challenge);
+        if previous.map(|c| c.challenge.lash_hash_at) == Some(last_hash_at) {
+            return;
+        }
+        let scores: Vec<(Pubkey, u32)> = self
+            .workers
+            .iter_mut()
+            .filter(|(_, worker)| worker.best_difficulty > 0)
+            .map(|(authority, worker)| (*authority, std::mem::take(&mut worker.best_difficulty)))
+            .collect();
+        if let (Some(previous), false) = (previous, scores.is_empty()) {
+            if self.unsettled.len() >= MAX_UNSETTLED_ROUNDS {
+                if let Some((last_hash_at, _)) = self.unsettled.pop_front() {
+                    log_dropped_round(last_hash_at);
+                }
+            }
+            self.unsettled
+                .push_back((previous.challenge.lash_hash_at, scores));
+        }
+        self.best_forwarded = 0;
+    }
+
+    /// Splits the member's reward for a round between its workers by share of work. Rounds
+    /// older than the event never received one of their own and are dropped.
+    fn settle(&mut self, event: &PoolMemberMiningEvent) -> bool {
+        while self
+            .unsettled
+            .front()
+            .is_some_and(|(last_hash_at, _)| *last_hash_at < event.last_hash_at)
+        {
+            if let Some((last_hash_at, _)) = self.unsettled.pop_front() {
+                log_dropped_round(last_hash_at);
+            }
+        }
+        if self
+            .unsettled
+            .front()
+            .map(|(last_hash_at, _)| *last_hash_at)
+            != Some(event.last_hash_at)
+        {
+            return false;
+        }
+        let Some((_, scores)) = self.unsettled.pop_front() else {
+            return false;
+        };
+        let total_work: u128 = scores.iter().map(|(_, d)| 1u128 << (*d).min(127)).sum();
+        for (authority, difficulty) in scores {
+            let work = 1u128 << difficulty.min(127);
+            let reward = (event.member_reward as u128 * work / total_work.max(1)) as u64;
+            if let Some(worker) = self.workers.get_mut(&authority) {
+                worker.last_difficulty = difficulty;
+                worker.last_reward = reward;
+                worker.total_reward += reward;
+            }
+            self.events
+                .insert(authority, worker_event(event, difficulty as u64, reward));
+        }
+        true
+    }
+
+    fn rows(&self) -> Vec<WorkerRow> {
+        let mut rows: Vec<WorkerRow> = self
+            .workers
+            .iter()
+            .map(|(authority, worker)| WorkerRow {
+                worker: authority.to_string(),
+                slot: worker.slot,
+                contributions: worker.contributions,
+                last_score: worker.last_difficulty,
+                last_reward: format!("{:#.11}", amount_u64_to_f64(worker.last_reward)),
+                total_reward: format!("{:#.11}", amount_u64_to_f64(worker.total_reward)),
+            })
+            .collect();
+        rows.sort_by_key(|row| row.slot);
+        rows
+    }
+}
+
+fn log_dropped_round(last_hash_at: i64) {
+    println!(
+        "{} Dropping worker scores for round {} without an upstream event",
+        "WARNING".bold().yellow(),
+        last_hash_at
+    );
+}
+
+#[derive(Serialize, Tabled)]
+struct WorkerRow {
+    #[tabled(rename = "Worker")]
+    worker: String,
+    #[tabled(rename = "Slot")]
+    slot: u64,
+    #[tabled(rename = "Contributions")]
+    contributions: u64,
+    #[tabled(rename = "Last Score")]
+    last_score: u32,
+    #[tabled(rename = "Last Reward")]
+    last_reward: String,
+    #[tabled(rename = "Total Reward")]
+    total_reward: String,
+}
+
+impl Miner {
+    pub async fn pool_proxy(&self, args: PoolArgs, proxy_args: PoolProxyArgs) -> Result<(), Error> {
+        let Some(pool_url) = args.pool_url else {
+            return Err(Error::Internal("a pool url is required".to_string()));
+        };
+        let upstream = Pool::new(pool_url);
+
+        // Register with upstream pool as a single member
+        let member = upstream.post_pool_register(self).await?;
+        let pool_address = upstream.get_pool_address().await?;
+        let state = Arc::new(Mutex::new(ProxyState {
+            pool_address: pool_address.address,
+            pool_bump: pool_address.bump,
+            member,
+            max_workers: proxy_args.max_workers.max(1),
+            workers: HashMap::new(),
+            challenge: None,
+            best_forwarded: 0,
+            unsettled: VecDeque::new(),
+            events: HashMap::new(),
+        }));
+
+        // Serve workers
+        let listener = TcpListener::bind(proxy_args.listen).await.map_err(|err| {
+            Error::Internal(format!("failed to bind {}: {}", proxy_args.listen, err))
+        })?;
+        println!(
+            "{} Proxying {} for up to {} workers on http://{}",
+            "INFO".bold().cyan(),
+            upstream.pool_url,
+            proxy_args.max_workers,
+            proxy_args.listen
+        );
+        tokio::spawn({
+            let miner = self.clone();
+            let upstream = upstream.clone();
+            let state = state.clone();
+            async move {
+                loop {
+                    let Ok((stream, _)) = listener.accept().await else {
+                        continue;
+                    };
+                    tokio::spawn(handle(
+                        stream,
+                        miner.clone(),
+                        upstream.clone(),
+                        state.clone(),
+                    ));
+                }
+            }
+        });
+
+        // Follow upstream rounds
+        let mut upstream_unreachable = false;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            match upstream.get_pool_challenge(self).await {
+                Ok(challenge) => {
+                    upstream_unreachable = false;
+                    state.lock().unwrap().update_challenge(challenge);
+                }
+                Err(err) => {
+                    if !upstream_unreachable {
+                        upstream_unreachable = true;
+                        println!(
+                            "{} Failed to fetch challenge from {}: {}",
+                            "WARNING".bold().yellow(),
+                            upstream.pool_url,
+                            err
+                        );
+                    }
+                    continue;
+                }
+            }
+
+            // Settle the last round once its event is published
+            if state.lock().unwrap().unsettled.is_empty() {
+                continue;
+            }
+            if let Ok(event) = upstream.get_pool_event(self).await {
+                let mut state = state.lock().unwrap();
+                if state.settle(&event) {
+                    print_workers(&state.rows());
+                }
+            }
+        }
+    }
+}
+
+async fn handle(
+    mut stream: TcpStream,
+    miner: Miner,
+    upstream: Pool,
+    state: Arc<Mutex<ProxyState>>,
+) {
+    let Some((method, path, body)) = read_request(&mut stream).await else {
+        return;
+    };
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (status, body) = match (method.as_str(), segments.as_slice()) {
+        ("POST", ["contribute"]) => contribute(&miner, &upstream, &state, &body).await,
+        (method, segments) => {
+            let mut state = state.lock().unwrap();
+            match (method, segments) {
+                ("GET", ["address"]) | ("GET", ["pool-address"]) => json(&PoolAddress {
+                    address: state.pool_address,
+                    bump: state.pool_bump,
+                }),
+                ("POST", ["register"]) => match serde_json::from_slice::<RegisterPayload>(&body) {
+                    Ok(payload) => match state.register(payload.authority) {
+                        Some(member) => json(&member),
+                        None => ("503 Service Unavailable", String::new()),
+                    },
+                    Err(_) => ("400 Bad Request", String::new()),
+                },
+                ("GET", ["challenge", _]) => match state.worker_challenge() {
+                    Some(challenge) => json(&challenge),
+                    None => ("503 Service Unavailable", String::new()),
+                },
+                ("GET", ["event", "latest", authority]) => {
+                    match authority
+                        .parse::<Pubkey>()
+                        .ok()
+                        .and_then(|authority| state.events.get(&authority))
+                    {
+                        Some(event) => json(event),
+                        None => ("404 Not Found", String::new()),
+                    }
+                }
+                ("GET", ["workers"]) => json(&state.rows()),
+                _ => ("404 Not Found", String::new()),
+            }
+        }
+    };
+    respond(&mut stream, status, &body).await;
+}
+
+/// Accepts a worker's solution and forwards it upstream if it is the best of the round.
+async fn contribute(
+    miner: &Miner,
+    upstream: &Pool,
+    state: &Mutex<ProxyState>,
+    body: &[u8],
+) -> (&'static str, String) {
+    let Ok(payload) = serde_json::from_slice::<ContributePayload>(body) else {
+        return ("400 Bad Request", String::new());
+    };
+    if !payload
+        .signature
+        .verify(payload.authority.as_ref(), &payload.solution.to_bytes())
+    {
+        return ("401 Unauthorized", String::new());
+    }
+    let forward = state
+        .lock()
+        .unwrap()
+        .contribute(&payload.authority, &payload.solution);
+    match forward {
+        Err(err) => ("400 Bad Request", err),
+        Ok(None) => ("200 OK", String::new()),
+        Ok(Some(previous)) => match upstream.post_pool_solution(miner, &payload.solution).await {
+            Ok(()) => ("200 OK", String::new()),
+            Err(err) => {
+                // Let a retry of the same solution be forwarded
+                let mut state = state.lock().unwrap();
+                if state.best_forwarded == payload.solution.to_hash().difficulty() {
+                    state.best_forwarded = previous;
+                }
+                match &err {
+                    Error::Pool(err) if err.status().is_some_and(|s| s.is_client_error()) => {
+                        ("400 Bad Request", err.to_string())
+                    }
+                    _ => ("502 Bad Gateway", err.to_string()),
+                }
+            }
+        },
+    }
+}
+
+/// A copy of the member's mining event with the reward and score of a single worker.
+fn worker_event(
+    event: &PoolMemberMiningEvent,
+    member_difficulty: u64,
+    member_reward: u64,
+) -> PoolMemberMiningEvent {
+    PoolMemberMiningEvent {
+        signature: event.signature,
+        block: event.block,
+        timestamp: event.timestamp,
+        balance: event.balance,
+        difficulty: event.difficulty,
+        last_hash_at: event.last_hash_at,
+        timing: event.timing,
+        net_reward: event.net_reward,
+        net_base_reward: event.net_base_reward,
+        net_miner_boost_reward: event.net_miner_boost_reward,
+        net_staker_boost_reward: event.net_staker_boost_reward,
+        member_difficulty,
+        member_reward,
+    }
+}
+
+fn print_workers(rows: &[WorkerRow]) {
+    let mut table = Table::new(rows);
+    table.with(Style::blank());
+    table.modify(Columns::new(1..), Alignment::right());
+    table.modify(Rows::first(), Color::BOLD);
+    table.with(Highlight::new(Rows::single(1)).color(BorderColor::default().top(Color::FG_WHITE)));
+    table.with(Highlight::new(Rows::single(1)).border(Border::new().top('━')));
+    println!("\n{}\n", table);
+}
@@ -0,0 +1,290 @@
+use std::str::FromStr;
+
+use colored::*;
+use futures::StreamExt;
+use ore_api::consts::ONE_ORE;
+use ore_pool_api::{instruction::PoolInstruction, state::member_pda};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer};
+use solana_transaction_status::UiTransactionEncoding;
+use tabled::{
+    settings::{
+        object::{Columns, Rows},
+        Alignment, Remove, Style,
+    },
+    Table,
+};
+
+use crate::{
+    args::{PoolArgs, PoolReconcileArgs},
+    error::Error,
+    utils::{
+        amount_f64_to_u64, amount_u64_to_f64, format_timestamp, JournalEntry, RoundKind,
+        RoundStatus, TableData, TableSectionTitle,
+    },
+    Miner,
+};
+
+use super::pool::Pool;
+
+/// Signatures fetched per page when scanning a member account's history.
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+/// Transactions fetched at once when scanning a member account's history.
+const TRANSACTION_FETCH_CONCURRENCY: usize = 8;
+
+/// A pool claim found on-chain.
+struct PoolClaim {
+    timestamp: i64,
+    amount: u64,
+}
+
+impl Miner {
+    pub async fn pool_reconcile(
+        &self,
+        args: PoolArgs,
+        reconcile_args: PoolReconcileArgs,
+    ) -> Result<(), Error> {
+        let Some(pool_url) = args.pool_url else {
+            return Err(Error::Internal("a pool url is required".to_string()));
+        };
+        let pool = Pool::new(pool_url);
+        let (from, to) = (reconcile_args.from, reconcile_args.to);
+        let tolerance = amount_f64_to_u64(reconcile_args.tolerance) as i128;
+
+        // Fetch balances reported by the pool and attributed on-chain
+        let pool_address = pool.get_pool_address().await?.address;
+        let member_address = member_pda(self.signer().pubkey(), pool_address).0;
+        let offchain = pool.get_pool_member(self).await?;
+        let onchain = pool.get_pool_member_onchain(self, pool_address).await?;
+
+        // Sum rewards from recorded mining events
+        let entries: Vec<JournalEntry> = self
+            .journal
+            .read()
+            .map_err(|err| Error::Internal(format!("failed to read journal: {}", err)))?
+            .into_iter()
+            .filter(|e| e.kind == RoundKind::Pool && e.status == RoundStatus::Confirmed)
+            .filter(|e| {
+                e.pool_url.as_deref().map_or(true, |url| {
+                    url.trim_end_matches('/') == pool.pool_url.trim_end_matches('/')
+                })
+            })
+            .collect();
+        let in_window = |timestamp: i64| {
+            from.map_or(true, |from| timestamp >= from) && to.map_or(true, |to| timestamp <= to)
+        };
+        let recorded_total: u64 = entries.iter().map(|e| e.my_reward()).sum();
+        let recorded: Vec<&JournalEntry> =
+            entries.iter().filter(|e| in_window(e.timestamp)).collect();
+        let recorded_window: u64 = recorded.iter().map(|e| e.my_reward()).sum();
+
+        // Sum claims made on-chain. Checking all claims needs the full history.
+        let until = if from.is_some() { to } else { None };
+        let claims = self.fetch_pool_claims(member_address, from, until).await?;
+        let claimed_total: u64 = claims.iter().map(|claim| claim.amount).sum();
+        let claims_window: Vec<&PoolClaim> = claims
+            .iter()
+            .filter(|claim| in_window(claim.timestamp))
+            .collect();
+        let claimed_window: u64 = claims_window.iter().map(|claim| claim.amount).sum();
+
+        // Aggregate balances
+        let mut data = vec![
+            TableData {
+                key: "Member".to_string(),
+                value: member_address.to_string(),
+            },
+            TableData {
+                key: "Reported by pool".to_string(),
+                value: format_ore(offchain.total_balance.max(0) as u64),
+            },
+            TableData {
+                key: "Attributed on-chain".to_string(),
+                value: format_ore(onchain.total_balance),
+            },
+            TableData {
+                key: "Unclaimed on-chain".to_string(),
+                value: format_ore(onchain.balance),
+            },
+            TableData {
+                key: "Recorded rewards".to_string(),
+                value: format_ore(recorded_total),
+            },
+        ];
+
+        // Aggregate window
+        let len1 = data.len();
+        data.push(TableData {
+            key: "From".to_string(),
+            value: from.map_or("–".to_string(), format_timestamp),
+        });
+        data.push(TableData {
+            key: "To".to_string(),
+            value: to.map_or("–".to_string(), format_timestamp),
+        });
+        data.push(TableData {
+            key: "Recorded rewards".to_string(),
+            value: format!(
+                "{} ({} rounds)",
+                format_ore(recorded_window),
+                recorded.len()
+            ),
+        });
+        data.push(TableData {
+            key: "Claims".to_string(),
+            value: format!(
+                "{} ({} claims)",
+                format_ore(claimed_window),
+                claims_window.len()
+            ),
+        });
+
+        // Check balances against each other
+        let len2 = data.len();
+        let mut discrepancies = 0;
+        let mut check = |key: &str, diff: i128, ok: bool| {
+            if !ok {
+                discrepancies += 1;
+            }
+            data.push(TableData {
+                key: key.to_string(),
+                value: if ok {
+                    format!("{} {}", format_signed(diff), "OK".bold().green())
+                } else {
+                    format!("{} {}", format_signed(diff), "MISMATCH".bold().red())
+                },
+            });
+        };
+        let pending = offchain.total_balance as i128 - onchain.total_balance as i128;
+        check("Pending attribution", pending, pending >= -tolerance);
+        let uncredited = offchain.total_balance as i128 - recorded_total as i128;
+        check(
+            "Reported less recorded",
+            uncredited,
+            uncredited >= -tolerance,
+        );
+        if from.is_none() {
+            let claimed = onchain.total_balance as i128 - onchain.balance as i128;
+            let diff = claimed - claimed_total as i128;
+            check("Claimed less claims", diff, diff.abs() <= tolerance);
+        }
+
+        // Print table
+        let mut table = Table::new(data);
+        table.with(Remove::row(Rows::first()));
+        table.modify(Columns::single(1), Alignment::right());
+        table.with(Style::blank());
+        table.section_title(0, "Balances");
+        table.section_title(len1, "Window");
+        table.section_title(len2, "Checks");
+        println!("\n{table}\n");
+        if discrepancies > 0 {
+            println!(
+                "{} Found {} discrepancies with {}",
+                "WARNING".bold().yellow(),
+                discrepancies,
+                pool.pool_url
+            );
+        } else {
+            println!("No discrepancies found with {}", pool.pool_url);
+        }
+        Ok(())
+    }
+
+    /// Scans a member account's transaction history for pool claims, newest first, between
+    /// `from` and `until` if given.
+    async fn fetch_pool_claims(
+        &self,
+        member_address: Pubkey,
+        from: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<PoolClaim>, Error> {
+        let mut claims = vec![];
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    &member_address,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: Some(SIGNATURE_PAGE_SIZE),
+                        commitment: None,
+                    },
+                )
+                .await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Signature::from_str(&last.signature).ok();
+            let done = page.len() < SIGNATURE_PAGE_SIZE
+                || from.is_some_and(|from| last.block_time.is_some_and(|t| t < from));
+
+            // Select successful transactions in the window
+            let signatures = page
+                .iter()
+                .filter(|status| status.err.is_none())
+                .filter_map(|status| {
+                    let timestamp = status.block_time?;
+                    let in_window = from.map_or(true, |from| timestamp >= from)
+                        && until.map_or(true, |until| timestamp <= until);
+                    let signature = Signature::from_str(&status.signature).ok()?;
+                    in_window.then_some((signature, timestamp))
+                });
+
+            // Fetch transactions concurrently
+            let txs: Vec<_> = futures::stream::iter(signatures)
+                .map(|(signature, timestamp)| async move {
+                    self.rpc_client
+                        .get_transaction_with_config(
+                            &signature,
+                            RpcTransactionConfig {
+                                encoding: Some(UiTransactionEncoding::Base64),
+                                commitment: None,
+                                max_supported_transaction_version: Some(0),
+                            },
+                        )
+                        .await
+                        .map(|tx| (tx, timestamp))
+                })
+                .buffered(TRANSACTION_FETCH_CONCURRENCY)
+                .collect()
+                .await;
+
+            // Decode claims
+            for result in txs {
+                let (tx, timestamp) = result?;
+                let Some(tx) = tx.transaction.transaction.decode() else {
+                    continue;
+                };
+                let keys = tx.message.static_account_keys();
+                for ix in tx.message.instructions() {
+                    let is_claim = keys.get(ix.program_id_index as usize)
+                        == Some(&ore_pool_api::ID)
+                        && ix.data.first() == Some(&(PoolInstruction::Claim as u8));
+                    if let (true, Some(amount)) = (is_claim, ix.data.get(1..9)) {
+                        claims.push(PoolClaim {
+                            timestamp,
+                            amount: u64::from_le_bytes(amount.try_into().unwrap()),
+                        });
+                    }
+                }
+            }
+            if done {
+                break;
+            }
+        }
+        Ok(claims)
+    }
+}
+
+fn format_ore(amount: u64) -> String {
+    format!("{:#.11} ORE", amount_u64_to_f64(amount))
+}
+
+fn format_signed(amount: i128) -> String {
+    format!("{:+.11} ORE", amount as f64 / ONE_ORE as f64)
+}
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use colored::*;
-use ore_api::state::Proof;
+use ore_api::{consts::MINT_ADDRESS, state::Proof};
 use ore_boost_api::state::{boost_pda, stake_pda, Boost, Config as BoostConfig, Stake};
 use solana_program::{program_pack::Pack, pubkey::Pubkey};
 use solana_sdk::signature::Signer;
@@ -17,12 +17,15 @@ use tabled::{
 };
 
 use crate::{
-    args::{StakeArgs, StakeClaimArgs, StakeCommand, StakeDepositArgs, StakeWithdrawArgs},
+    args::{
+        StakeArgs, StakeClaimArgs, StakeCommand, StakeCompoundArgs, StakeDepositArgs,
+        StakeWithdrawArgs,
+    },
     error::Error,
     utils::{
-        amount_u64_to_f64, format_timestamp, get_boost, get_boost_config, get_boost_stake_accounts,
-        get_boosts, get_mint, get_proof_with_authority, get_stake, ComputeBudget, TableData,
-        TableSectionTitle,
+        amount_f64_to_u64, amount_u64_to_f64, format_timestamp, get_boost, get_boost_config,
+        get_boost_stake_accounts, get_boosts, get_mint, get_proof_with_authority, get_stake,
+        ComputeBudget, TableData, TableSectionTitle,
     },
     Miner, StakeAccountsArgs,
 };
@@ -39,6 +42,11 @@ impl Miner {
                 StakeCommand::Accounts(subargs) => {
                     self.stake_accounts(subargs, args).await.unwrap()
                 }
+                StakeCommand::Compound(subargs) => {
+                    if let Err(err) = self.stake_compound(subargs, args).await {
+                        println!("{} {}", "ERROR".bold().red(), err);
+                    }
+                }
             }
         } else {
             if let Some(mint) = args.mint {
@@ -107,13 +115,120 @@ impl Miner {
 
         // Send and confirm transaction
         println!("Claiming staking yield...");
-        self.send_and_confirm(&ixs, ComputeBudget::Fixed(100_000), false)
+        self.send_and_confirm(&ixs, ComputeBudget::Dynamic(100_000), false)
             .await
             .ok();
 
         Ok(())
     }
 
+    async fn stake_compound(
+        &self,
+        args: StakeCompoundArgs,
+        stake_args: StakeArgs,
+    ) -> Result<(), Error> {
+        // Only ORE can be redeposited, so default to it
+        let mint_address = match stake_args.mint {
+            Some(mint_str) => Pubkey::from_str(&mint_str)?,
+            None => MINT_ADDRESS,
+        };
+        if mint_address != MINT_ADDRESS {
+            return Err(Error::Internal(
+                "only ORE stake can be compounded".to_string(),
+            ));
+        }
+        let min_amount = amount_f64_to_u64(args.min_amount);
+
+        // Compound on an interval
+        loop {
+            if let Err(err) = self.stake_compound_once(mint_address, min_amount).await {
+                println!("{} Failed to compound: {}", "ERROR".bold().red(), err);
+            }
+            tokio::time::sleep(args.interval).await;
+        }
+    }
+
+    async fn stake_compound_once(
+        &self,
+        mint_address: Pubkey,
+        min_amount: u64,
+    ) -> Result<(), Error> {
+        let signer = self.signer();
+        let pubkey = signer.pubkey();
+        let boost_address = boost_pda(mint_address).0;
+        let stake_address = stake_pda(pubkey, boost_address).0;
+
+        // Get mining rewards
+        let mining_rewards = get_proof_with_authority(&self.rpc_client, pubkey)
+            .await
+            .map(|proof| proof.balance)
+            .unwrap_or(0);
+
+        // Get staking yield
+        let stake = get_stake(&self.rpc_client, stake_address).await.ok();
+        let staking_yield = match stake {
+            Some(stake) => {
+                let boost_config_address = ore_boost_api::state::config_pda().0;
+                let boost = get_boost(&self.rpc_client, boost_address)
+                    .await
+                    .map_err(|err| Error::Internal(format!("failed to fetch boost: {}", err)))?;
+                let boost_config = get_boost_config(&self.rpc_client).await;
+                let boost_proof = get_proof_with_authority(&self.rpc_client, boost_config_address)
+                    .await
+                    .map_err(|err| {
+                        Error::Internal(format!("failed to fetch boost proof: {}", err))
+                    })?;
+                calculate_claimable_yield(boost, boost_config, boost_proof, stake)
+            }
+            None => 0,
+        };
+
+        // Skip compounds that are not worth the fees
+        let amount = mining_rewards + staking_yield;
+        if amount < min_amount {
+            println!(
+                "{} ORE available to compound. Waiting for at least {} ORE...",
+                amount_u64_to_f64(amount),
+                amount_u64_to_f64(min_amount)
+            );
+            return Ok(());
+        }
+
+        // Build instructions
+        let beneficiary = self.initialize_ata(pubkey).await;
+        let mut ixs = vec![];
+        let mut compute_budget = 200_000;
+        if stake.is_none() {
+            ixs.push(ore_boost_api::sdk::open(pubkey, pubkey, mint_address));
+            compute_budget += 50_000;
+        }
+        if mining_rewards > 0 {
+            ixs.push(ore_api::sdk::claim(pubkey, beneficiary, mining_rewards));
+            compute_budget += 32_000;
+        }
+        if staking_yield > 0 {
+            ixs.push(ore_boost_api::sdk::claim(
+                pubkey,
+                beneficiary,
+                mint_address,
+                staking_yield,
+            ));
+            compute_budget += 100_000;
+        }
+        ixs.push(ore_boost_api::sdk::deposit(pubkey, mint_address, amount));
+
+        // Send and confirm transaction
+        println!(
+            "Compounding {} ORE ({} ORE mined, {} ORE yield)...",
+            amount_u64_to_f64(amount),
+            amount_u64_to_f64(mining_rewards),
+            amount_u64_to_f64(staking_yield)
+        );
+        self.send_and_confirm(&ixs, ComputeBudget::Dynamic(compute_budget), false)
+            .await?;
+        Ok(())
+    }
+
     async fn stake_get(&self, mint: String, authority: Option<String>) -> Result<(), Error> {
         // Fetch onchain data
         let mint_address = Pubkey::from_str(&mint).expect("Failed to parse mint address");
@@ -416,7 +531,7 @@ impl Miner {
         {
             println!("Initializing stake account...");
             let ix = ore_boost_api::sdk::open(signer.pubkey(), signer.pubkey(), mint_address);
-            self.send_and_confirm(&[ix], ComputeBudget::Fixed(50_000), false)
+            self.send_and_confirm(&[ix], ComputeBudget::Dynamic(50_000), false)
                 .await
                 .ok();
         }
@@ -424,7 +539,7 @@ impl Miner {
         // Send tx
         println!("Depositing stake...");
         let ix = ore_boost_api::sdk::deposit(signer.pubkey(), mint_address, amount);
-        self.send_and_confirm(&[ix], ComputeBudget::Fixed(200_000), false)
+        self.send_and_confirm(&[ix], ComputeBudget::Dynamic(200_000), false)
             .await
             .ok();
         Ok(())
@@ -502,7 +617,7 @@ impl Miner {
             mint_address,
             amount,
         ));
-        self.send_and_confirm(&ixs, ComputeBudget::Fixed(200_000), false)
+        self.send_and_confirm(&ixs, ComputeBudget::Dynamic(200_000), false)
             .await
             .ok();
 
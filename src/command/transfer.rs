@@ -70,7 +70,7 @@ impl Miner {
             )
             .unwrap(),
         );
-        self.send_and_confirm(&ixs, ComputeBudget::Fixed(32_000), false)
+        self.send_and_confirm(&ixs, ComputeBudget::Dynamic(32_000), false)
             .await
             .ok();
     }
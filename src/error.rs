@@ -4,6 +4,8 @@ pub enum Error {
     Internal(String),
     #[error("reqwest")]
     Reqwest(#[from] reqwest::Error),
+    #[error("{0}")]
+    Pool(#[from] crate::pool_client::PoolError),
     #[error("solana parse pubkey")]
     SolanaParsePubkey(#[from] solana_sdk::pubkey::ParsePubkeyError),
     #[error("solana rpc client")]
@@ -14,4 +16,6 @@ pub enum Error {
     ParseInt(#[from] std::num::ParseIntError),
     #[error("number of devices per keypair exceeded")]
     TooManyDevices,
+    #[error("invalid core selection: {0}")]
+    InvalidCores(String),
 }
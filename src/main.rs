@@ -1,8 +1,11 @@
 mod args;
 mod command;
 mod error;
+mod pool_client;
 mod send;
+mod simulate;
 mod utils;
+mod worker;
 
 use futures::StreamExt;
 use std::{sync::Arc, sync::RwLock};
@@ -16,7 +19,10 @@ use solana_sdk::{
     commitment_config::CommitmentConfig,
     signature::{read_keypair_file, Keypair},
 };
-use utils::{PoolMiningData, SoloMiningData, Tip};
+use utils::{
+    default_journal_path, AdaptiveBuffer, Journal, LogFormat, Metrics, Notifier, PoolMiningData,
+    Shutdown, SoloMiningData, Tip,
+};
 
 // TODO: Unify balance and proof into "account"
 // TODO: Move balance subcommands to "pool"
@@ -29,12 +35,19 @@ struct Miner {
     pub priority_fee: Option<u64>,
     pub dynamic_fee_url: Option<String>,
     pub dynamic_fee: bool,
+    pub compute_unit_margin: u64,
     pub rpc_client: Arc<RpcClient>,
     pub fee_payer_filepath: Option<String>,
     pub jito_client: Arc<RpcClient>,
     pub tip: Arc<std::sync::RwLock<u64>>,
     pub solo_mining_data: Arc<std::sync::RwLock<Vec<SoloMiningData>>>,
     pub pool_mining_data: Arc<std::sync::RwLock<Vec<PoolMiningData>>>,
+    pub journal: Journal,
+    pub log_format: LogFormat,
+    pub metrics: Arc<Metrics>,
+    pub shutdown: Arc<Shutdown>,
+    pub adaptive_buffer: Arc<AdaptiveBuffer>,
+    pub notifier: Arc<Notifier>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -48,6 +61,12 @@ enum Commands {
     #[command(about = "Claim your mining yield")]
     Claim(ClaimArgs),
 
+    #[command(about = "Estimate expected earnings and costs for your machine")]
+    Estimate(EstimateArgs),
+
+    #[command(about = "Query and summarize your mining history")]
+    History(HistoryArgs),
+
     #[cfg(feature = "admin")]
     #[command(about = "Initialize the program")]
     Initialize(InitializeArgs),
@@ -124,9 +143,57 @@ struct Args {
     )]
     dynamic_fee_url: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "JOURNAL_FILEPATH",
+        help = "Filepath to the mining journal. Defaults to the ore directory next to your Solana config.",
+        global = true
+    )]
+    journal: Option<String>,
+
+    #[arg(
+        long,
+        help = "Run without redrawing the terminal. Implies the text log format unless another one is set.",
+        global = true
+    )]
+    headless: bool,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "How to report mining rounds. The text and json formats print one record per round.",
+        global = true
+    )]
+    log_format: Option<LogFormat>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "URL to POST a JSON payload to on miner events such as confirmed or failed rounds.",
+        global = true
+    )]
+    notify_url: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "Shell command to run on miner events. The event is passed in ORE_* environment variables.",
+        global = true
+    )]
+    notify_exec: Option<String>,
+
     #[arg(long, help = "Enable dynamic priority fees", global = true)]
     dynamic_fee: bool,
 
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        help = "Headroom to add to the compute units a transaction consumes in simulation when setting its compute unit limit.",
+        default_value = "10",
+        global = true
+    )]
+    compute_unit_margin: u64,
+
     #[arg(
         long,
         value_name = "JITO",
@@ -167,6 +234,16 @@ async fn main() {
     let tip_clone = Arc::clone(&tip);
     let solo_mining_data = Arc::new(RwLock::new(Vec::new()));
     let pool_mining_data = Arc::new(RwLock::new(Vec::new()));
+    let journal = Journal::new(
+        args.journal
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(default_journal_path),
+    );
+    let log_format = match args.log_format {
+        Some(LogFormat::Table) | None if args.headless => LogFormat::Text,
+        Some(log_format) => log_format,
+        None => LogFormat::Table,
+    };
 
     if args.jito {
         let url = "ws://bundles-api-rest.jito.wtf/api/v1/bundles/tip_stream";
@@ -193,14 +270,19 @@ async fn main() {
         Some(default_keypair),
         args.dynamic_fee_url,
         args.dynamic_fee,
+        args.compute_unit_margin,
         Some(fee_payer_filepath),
         Arc::new(jito_client),
         tip,
         solo_mining_data,
         pool_mining_data,
+        journal,
+        log_format,
+        Notifier::new(args.notify_url, args.notify_exec),
     ));
 
     // Execute user command.
+    let jito = args.jito;
     match args.command {
         Commands::Account(args) => {
             miner.account(args).await;
@@ -213,6 +295,12 @@ async fn main() {
                 println!("{:?}", err);
             }
         }
+        Commands::Estimate(args) => {
+            miner.estimate(args, jito).await;
+        }
+        Commands::History(args) => {
+            miner.history(args);
+        }
         Commands::Pool(args) => {
             miner.pool(args).await;
         }
@@ -247,11 +335,15 @@ impl Miner {
         keypair_filepath: Option<String>,
         dynamic_fee_url: Option<String>,
         dynamic_fee: bool,
+        compute_unit_margin: u64,
         fee_payer_filepath: Option<String>,
         jito_client: Arc<RpcClient>,
         tip: Arc<std::sync::RwLock<u64>>,
         solo_mining_data: Arc<std::sync::RwLock<Vec<SoloMiningData>>>,
         pool_mining_data: Arc<std::sync::RwLock<Vec<PoolMiningData>>>,
+        journal: Journal,
+        log_format: LogFormat,
+        notifier: Notifier,
     ) -> Self {
         Self {
             rpc_client,
@@ -259,11 +351,18 @@ impl Miner {
             priority_fee,
             dynamic_fee_url,
             dynamic_fee,
+            compute_unit_margin,
             fee_payer_filepath,
             jito_client,
             tip,
             solo_mining_data,
             pool_mining_data,
+            journal,
+            log_format,
+            metrics: Arc::new(Metrics::default()),
+            shutdown: Arc::new(Shutdown::default()),
+            adaptive_buffer: Arc::new(AdaptiveBuffer::default()),
+            notifier: Arc::new(notifier),
         }
     }
 
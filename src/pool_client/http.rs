@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use ore_pool_types::{
+    BalanceUpdate, ContributePayload, Member, MemberChallenge, PoolAddress, PoolMemberMiningEvent,
+    RegisterPayload, UpdateBalancePayload,
+};
+use serde::de::DeserializeOwned;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::{mpsc, watch, OnceCell};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+use super::{PoolClient, PoolError, PoolFeed, PoolMessage, PoolProtocol};
+
+/// How long to wait for a pool server to accept a connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for a pool server to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An HTTP client that gives up on pool servers that stop answering.
+pub fn pool_http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("Failed to build http client")
+}
+
+/// A pool server reached over HTTP, with pushed updates over a WebSocket at
+/// `/subscribe/{authority}` when the server supports it.
+pub struct HttpPoolClient {
+    http_client: reqwest::Client,
+    url: String,
+    protocol: OnceCell<PoolProtocol>,
+}
+
+impl HttpPoolClient {
+    pub fn new(url: String) -> Self {
+        Self {
+            http_client: pool_http_client(),
+            url: url.trim_end_matches('/').to_string(),
+            protocol: OnceCell::new(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, PoolError> {
+        let resp = self
+            .http_client
+            .get(format!("{}{}", self.url, path))
+            .send()
+            .await;
+        parse(check(resp).await?).await
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &impl serde::Serialize,
+    ) -> Result<reqwest::Response, PoolError> {
+        let resp = self
+            .http_client
+            .post(format!("{}{}", self.url, path))
+            .json(body)
+            .send()
+            .await;
+        check(resp).await
+    }
+}
+
+#[async_trait]
+impl PoolClient for HttpPoolClient {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    async fn protocol(&self) -> Result<PoolProtocol, PoolError> {
+        // Older servers reject the current address path
+        self.protocol
+            .get_or_try_init(|| async {
+                match self.get::<PoolAddress>("/address").await {
+                    Ok(_) => Ok(PoolProtocol::Current),
+                    Err(err) if err.status().is_some_and(|s| s.is_client_error()) => {
+                        self.get::<PoolAddress>("/pool-address").await?;
+                        Ok(PoolProtocol::Legacy)
+                    }
+                    Err(err) => Err(err),
+                }
+            })
+            .await
+            .copied()
+    }
+
+    async fn address(&self) -> Result<PoolAddress, PoolError> {
+        match self.protocol().await? {
+            PoolProtocol::Current => self.get("/address").await,
+            PoolProtocol::Legacy => self.get("/pool-address").await,
+        }
+    }
+
+    async fn register(&self, authority: Pubkey) -> Result<Member, PoolError> {
+        parse(
+            self.post("/register", &RegisterPayload { authority })
+                .await?,
+        )
+        .await
+    }
+
+    async fn member(&self, authority: Pubkey) -> Result<Member, PoolError> {
+        self.get(&format!("/member/{}", authority)).await
+    }
+
+    async fn challenge(&self, authority: Pubkey) -> Result<MemberChallenge, PoolError> {
+        self.get(&format!("/challenge/{}", authority)).await
+    }
+
+    async fn contribute(&self, payload: &ContributePayload) -> Result<(), PoolError> {
+        self.post("/contribute", payload).await?;
+        Ok(())
+    }
+
+    async fn latest_event(&self, authority: Pubkey) -> Result<PoolMemberMiningEvent, PoolError> {
+        self.get(&format!("/event/latest/{}", authority)).await
+    }
+
+    async fn commit(&self, payload: &UpdateBalancePayload) -> Result<BalanceUpdate, PoolError> {
+        let path = match self.protocol().await? {
+            PoolProtocol::Current => "/commit",
+            PoolProtocol::Legacy => "/update-balance",
+        };
+        parse(self.post(path, payload).await?).await
+    }
+
+    async fn subscribe(&self, authority: Pubkey) -> Option<PoolFeed> {
+        let url = format!(
+            "{}/subscribe/{}",
+            self.url.replacen("http", "ws", 1),
+            authority
+        );
+        let (mut stream, _) = tokio::time::timeout(CONNECT_TIMEOUT, connect_async(url))
+            .await
+            .ok()?
+            .ok()?;
+        let (challenge_tx, challenges) = watch::channel(None);
+        let (event_tx, events) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = stream.next().await {
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let sent = match serde_json::from_str::<PoolMessage>(&text) {
+                    Ok(PoolMessage::Challenge(challenge)) => {
+                        challenge_tx.send(Some(challenge)).is_ok()
+                    }
+                    Ok(PoolMessage::Event(event)) => event_tx.send(event).is_ok(),
+                    Err(_) => true,
+                };
+                if !sent {
+                    break;
+                }
+            }
+        });
+        Some(PoolFeed::new(self.url.clone(), challenges, events))
+    }
+}
+
+/// Turns a failed request or error status into a typed error.
+async fn check(
+    resp: Result<reqwest::Response, reqwest::Error>,
+) -> Result<reqwest::Response, PoolError> {
+    let resp = resp.map_err(|err| PoolError::Unreachable(err.to_string()))?;
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let message = resp.text().await.unwrap_or_default();
+    Err(PoolError::Status {
+        status,
+        message: message.trim().to_string(),
+    })
+}
+
+async fn parse<T: DeserializeOwned>(resp: reqwest::Response) -> Result<T, PoolError> {
+    resp.json::<T>()
+        .await
+        .map_err(|err| PoolError::InvalidResponse(err.to_string()))
+}
@@ -0,0 +1,171 @@
+mod http;
+
+pub use http::*;
+
+use async_trait::async_trait;
+use ore_pool_types::{
+    BalanceUpdate, ContributePayload, Member, MemberChallenge, PoolAddress, PoolMemberMiningEvent,
+    UpdateBalancePayload,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::{mpsc, watch};
+
+/// Generations of the pool server protocol, which serve some endpoints at different paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolProtocol {
+    /// Serves the pool address at `/address` and commits at `/commit`.
+    Current,
+    /// Serves the pool address at `/pool-address` and commits at `/update-balance`.
+    Legacy,
+}
+
+/// A failed request to a pool server.
+#[derive(Debug, thiserror::Error)]
+pub enum PoolError {
+    /// The request did not reach the server, or the server did not answer.
+    #[error("pool server unreachable: {0}")]
+    Unreachable(String),
+    /// The server answered with an error status.
+    #[error("pool server responded {status}{}", detail(.message))]
+    Status { status: StatusCode, message: String },
+    /// The server answered with a body that could not be parsed.
+    #[error("invalid response from pool server: {0}")]
+    InvalidResponse(String),
+}
+
+impl PoolError {
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            PoolError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Whether the request may succeed if retried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PoolError::Unreachable(_) => true,
+            PoolError::Status { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            PoolError::InvalidResponse(_) => false,
+        }
+    }
+}
+
+fn detail(message: &str) -> String {
+    if message.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", message)
+    }
+}
+
+/// The pool server protocol, as spoken by a member. Implemented over HTTP by
+/// [`HttpPoolClient`] and in memory by the simulated pool.
+#[async_trait]
+pub trait PoolClient: Send + Sync {
+    /// Where the server is reached, for display.
+    fn url(&self) -> &str;
+
+    /// The protocol generation the server speaks.
+    async fn protocol(&self) -> Result<PoolProtocol, PoolError>;
+
+    async fn address(&self) -> Result<PoolAddress, PoolError>;
+
+    /// Registers `authority` as a member. Returns the existing member if it is already
+    /// registered.
+    async fn register(&self, authority: Pubkey) -> Result<Member, PoolError>;
+
+    async fn member(&self, authority: Pubkey) -> Result<Member, PoolError>;
+
+    async fn challenge(&self, authority: Pubkey) -> Result<MemberChallenge, PoolError>;
+
+    async fn contribute(&self, payload: &ContributePayload) -> Result<(), PoolError>;
+
+    /// The mining event of the last round the pool landed.
+    async fn latest_event(&self, authority: Pubkey) -> Result<PoolMemberMiningEvent, PoolError>;
+
+    /// Submits a member-signed attribution of its balance for the operator to land.
+    async fn commit(&self, payload: &UpdateBalancePayload) -> Result<BalanceUpdate, PoolError>;
+
+    /// Subscribes to challenges and events pushed by the server. Returns None if the server
+    /// does not push them, in which case callers should poll instead.
+    async fn subscribe(&self, authority: Pubkey) -> Option<PoolFeed>;
+}
+
+/// A message pushed by a pool server to its subscribers.
+///
+/// Pushing updates is an optional extension of the pool protocol. A server that supports it
+/// accepts WebSocket upgrades at `/subscribe/{authority}` and sends each message as a JSON
+/// text frame, tagged with its `type` and flattened with its payload:
+///
+/// ```text
+/// {"type": "challenge", "challenge": {...}, "num_total_members": 8, ...}
+/// {"type": "event", "balance": 10, "difficulty": 21, "last_hash_at": 1700000000, ...}
+/// ```
+///
+/// Frames of other types are ignored. Servers that refuse the upgrade are polled instead.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PoolMessage {
+    Challenge(MemberChallenge),
+    Event(PoolMemberMiningEvent),
+}
+
+/// New challenges and mining events pushed by a pool server, to avoid polling for them.
+pub struct PoolFeed {
+    pub pool_url: String,
+    challenges: watch::Receiver<Option<MemberChallenge>>,
+    events: mpsc::UnboundedReceiver<PoolMemberMiningEvent>,
+}
+
+impl PoolFeed {
+    pub fn new(
+        pool_url: String,
+        challenges: watch::Receiver<Option<MemberChallenge>>,
+        events: mpsc::UnboundedReceiver<PoolMemberMiningEvent>,
+    ) -> Self {
+        Self {
+            pool_url,
+            challenges,
+            events,
+        }
+    }
+
+    /// Whether the connection to the pool server has closed.
+    pub fn is_closed(&self) -> bool {
+        self.challenges.has_changed().is_err()
+    }
+
+    /// Waits for a challenge newer than `last_hash_at`. Returns None if the feed closes.
+    pub async fn next_challenge(&mut self, last_hash_at: i64) -> Option<MemberChallenge> {
+        loop {
+            if let Some(challenge) = *self.challenges.borrow_and_update() {
+                if challenge.challenge.lash_hash_at != last_hash_at {
+                    return Some(challenge);
+                }
+            }
+            self.challenges.changed().await.ok()?;
+        }
+    }
+
+    /// Waits up to `timeout` for the event of the round started at `last_hash_at`.
+    pub async fn next_event(
+        &mut self,
+        last_hash_at: i64,
+        timeout: std::time::Duration,
+    ) -> Option<PoolMemberMiningEvent> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let event = tokio::time::timeout_at(deadline, self.events.recv())
+                .await
+                .ok()??;
+            if event.last_hash_at >= last_hash_at {
+                return Some(event);
+            }
+        }
+    }
+}
@@ -1,2 +1,6 @@
+mod priority_fee;
 mod send_and_confirm;
-mod priority_fee;
\ No newline at end of file
+mod watchdog;
+
+pub use send_and_confirm::MIN_SOL_BALANCE;
+pub use watchdog::*;
@@ -4,9 +4,10 @@ use colored::*;
 use indicatif::ProgressBar;
 use ore_api::error::OreError;
 use rand::seq::SliceRandom;
+use serde_json::json;
 use solana_client::{
     client_error::{ClientError, ClientErrorKind, Result as ClientResult},
-    rpc_config::RpcSendTransactionConfig,
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
 };
 use solana_program::{
     instruction::Instruction,
@@ -14,7 +15,6 @@ use solana_program::{
     pubkey::Pubkey,
     system_instruction::transfer,
 };
-use solana_rpc_client::spinner;
 use solana_sdk::{
     commitment_config::CommitmentLevel,
     compute_budget::ComputeBudgetInstruction,
@@ -23,17 +23,21 @@ use solana_sdk::{
 };
 use solana_transaction_status::{TransactionConfirmationStatus, UiTransactionEncoding};
 
-use crate::utils::{get_latest_blockhash_with_retries, ComputeBudget};
+use crate::utils::{get_latest_blockhash_with_retries, ComputeBudget, NotifyEvent};
 use crate::Miner;
 
-const MIN_SOL_BALANCE: f64 = 0.005;
+pub const MIN_SOL_BALANCE: f64 = 0.005;
 
 const RPC_RETRIES: usize = 0;
-const _SIMULATION_RETRIES: usize = 4;
+const SIMULATION_RETRIES: usize = 4;
 const GATEWAY_RETRIES: usize = 150;
 const CONFIRM_RETRIES: usize = 8;
 
+/// Largest compute unit limit a transaction may request.
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
 const CONFIRM_DELAY: u64 = 500;
+const SIMULATION_DELAY: u64 = 500;
 const GATEWAY_DELAY: u64 = 0;
 
 impl Miner {
@@ -43,25 +47,19 @@ impl Miner {
         compute_budget: ComputeBudget,
         skip_confirm: bool,
     ) -> ClientResult<Signature> {
-        let progress_bar = spinner::new_progress_bar();
+        let progress_bar = self.log_format.progress_bar();
         let signer = self.signer();
         let client = self.rpc_client.clone();
         let fee_payer = self.fee_payer();
         let mut send_client = self.rpc_client.clone();
 
         // Return error, if balance is zero
-        self.check_balance().await;
+        self.check_balance().await?;
 
-        // Set compute budget
-        let mut final_ixs = vec![];
-        match compute_budget {
-            ComputeBudget::Dynamic => {
-                todo!("simulate tx")
-            }
-            ComputeBudget::Fixed(cus) => {
-                final_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(cus))
-            }
-        }
+        // Set compute budget, simulating with the largest limit
+        let mut final_ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            MAX_COMPUTE_UNIT_LIMIT,
+        )];
 
         // Set compute unit price
         final_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
@@ -101,6 +99,29 @@ impl Miner {
             progress_bar.println(format!("  Jito tip: {} SOL", lamports_to_sol(jito_tip)));
         }
 
+        // Size compute budget to a simulation
+        let ComputeBudget::Dynamic(fallback) = compute_budget;
+        let cus = match self
+            .simulate_compute_units(&final_ixs, &fee_payer.pubkey())
+            .await
+        {
+            Ok(cus) => {
+                progress_bar.println(format!("  Compute units: {}", cus));
+                cus
+            }
+            Err(err) => {
+                log_warning(
+                    &progress_bar,
+                    &format!(
+                        "{} Falling back to static value: {} compute units",
+                        err, fallback
+                    ),
+                );
+                fallback
+            }
+        };
+        final_ixs[0] = ComputeBudgetInstruction::set_compute_unit_limit(cus);
+
         // Build tx
         let send_cfg = RpcSendTransactionConfig {
             skip_preflight: true,
@@ -237,6 +258,7 @@ impl Miner {
 
                             // Handle confirmation errors
                             Err(err) => {
+                                self.metrics.record_rpc_error();
                                 log_error(&progress_bar, &err.kind().to_string(), false);
                             }
                         }
@@ -245,6 +267,7 @@ impl Miner {
 
                 // Handle submit errors
                 Err(err) => {
+                    self.metrics.record_rpc_error();
                     log_error(&progress_bar, &err.kind().to_string(), false);
                 }
             }
@@ -253,6 +276,10 @@ impl Miner {
             tokio::time::sleep(Duration::from_millis(GATEWAY_DELAY)).await;
             if attempts > GATEWAY_RETRIES {
                 log_error(&progress_bar, "Max retries", true);
+                self.notifier.notify(
+                    NotifyEvent::MaxRetries,
+                    json!({ "attempts": attempts, "fee_payer": fee_payer.pubkey().to_string() }),
+                );
                 return Err(ClientError {
                     request: None,
                     kind: ClientErrorKind::Custom("Max retries".into()),
@@ -261,7 +288,7 @@ impl Miner {
         }
     }
 
-    pub async fn check_balance(&self) {
+    pub async fn check_balance(&self) -> ClientResult<()> {
         // Throw error if balance is less than min
         if let Ok(balance) = self
             .rpc_client
@@ -269,71 +296,76 @@ impl Miner {
             .await
         {
             if balance <= sol_to_lamports(MIN_SOL_BALANCE) {
-                panic!(
-                    "{} Insufficient balance: {} SOL\nPlease top up with at least {} SOL",
-                    "ERROR".bold().red(),
-                    lamports_to_sol(balance),
-                    MIN_SOL_BALANCE
-                );
+                return Err(ClientError {
+                    request: None,
+                    kind: ClientErrorKind::Custom(format!(
+                        "Insufficient balance: {} SOL. Please top up with at least {} SOL",
+                        lamports_to_sol(balance),
+                        MIN_SOL_BALANCE
+                    )),
+                });
             }
         }
+        Ok(())
     }
 
-    // TODO
-    fn _simulate(&self) {
-
-        // Simulate tx
-        // let mut sim_attempts = 0;
-        // 'simulate: loop {
-        //     let sim_res = client
-        //         .simulate_transaction_with_config(
-        //             &tx,
-        //             RpcSimulateTransactionConfig {
-        //                 sig_verify: false,
-        //                 replace_recent_blockhash: true,
-        //                 commitment: Some(self.rpc_client.commitment()),
-        //                 encoding: Some(UiTransactionEncoding::Base64),
-        //                 accounts: None,
-        //                 min_context_slot: Some(slot),
-        //                 inner_instructions: false,
-        //             },
-        //         )
-        //         .await;
-        //     match sim_res {
-        //         Ok(sim_res) => {
-        //             if let Some(err) = sim_res.value.err {
-        //                 println!("Simulaton error: {:?}", err);
-        //                 sim_attempts += 1;
-        //             } else if let Some(units_consumed) = sim_res.value.units_consumed {
-        //                 if dynamic_cus {
-        //                     println!("Dynamic CUs: {:?}", units_consumed);
-        //                     let cu_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(
-        //                         units_consumed as u32 + 1000,
-        //                     );
-        //                     let cu_price_ix =
-        //                         ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee);
-        //                     let mut final_ixs = vec![];
-        //                     final_ixs.extend_from_slice(&[cu_budget_ix, cu_price_ix]);
-        //                     final_ixs.extend_from_slice(ixs);
-        //                     tx = Transaction::new_with_payer(&final_ixs, Some(&signer.pubkey()));
-        //                 }
-        //                 break 'simulate;
-        //             }
-        //         }
-        //         Err(err) => {
-        //             println!("Simulaton error: {:?}", err);
-        //             sim_attempts += 1;
-        //         }
-        //     }
-
-        //     // Abort if sim fails
-        //     if sim_attempts.gt(&SIMULATION_RETRIES) {
-        //         return Err(ClientError {
-        //             request: None,
-        //             kind: ClientErrorKind::Custom("Simulation failed".into()),
-        //         });
-        //     }
-        // }
+    /// The compute unit limit `send_and_confirm` would request for `ixs`, sized to a
+    /// simulation against the latest state.
+    pub async fn estimate_compute_units(&self, ixs: &[Instruction]) -> Result<u32, String> {
+        let mut final_ixs = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            ComputeBudgetInstruction::set_compute_unit_price(self.priority_fee.unwrap_or(0)),
+        ];
+        final_ixs.extend_from_slice(ixs);
+        self.simulate_compute_units(&final_ixs, &self.fee_payer().pubkey())
+            .await
+    }
+
+    /// Simulates a transaction of `ixs` against the latest state and returns the compute
+    /// units it consumes, plus the configured margin.
+    async fn simulate_compute_units(
+        &self,
+        ixs: &[Instruction],
+        fee_payer: &Pubkey,
+    ) -> Result<u32, String> {
+        let tx = Transaction::new_with_payer(ixs, Some(fee_payer));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc_client.commitment()),
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..Default::default()
+        };
+
+        // Simulate tx, retrying rpc errors
+        let mut attempts = 0;
+        let result = loop {
+            match self
+                .rpc_client
+                .simulate_transaction_with_config(&tx, config.clone())
+                .await
+            {
+                Ok(response) => break response.value,
+                Err(err) => {
+                    self.metrics.record_rpc_error();
+                    attempts += 1;
+                    if attempts > SIMULATION_RETRIES {
+                        return Err(format!("Simulation failed: {}", err.kind()));
+                    }
+                    tokio::time::sleep(Duration::from_millis(SIMULATION_DELAY)).await;
+                }
+            }
+        };
+        if let Some(err) = result.err {
+            return Err(format!("Simulation failed: {}", err));
+        }
+        let units_consumed = result
+            .units_consumed
+            .ok_or("Simulation did not report compute units")?;
+
+        // Add margin
+        let cus = units_consumed.saturating_mul(100 + self.compute_unit_margin) / 100;
+        Ok(cus.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32)
     }
 }
 
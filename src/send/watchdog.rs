@@ -0,0 +1,193 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{NaiveDate, Utc};
+use colored::*;
+use solana_program::{
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    system_instruction::transfer,
+};
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use serde_json::json;
+
+use crate::{utils::NotifyEvent, Miner};
+
+use super::MIN_SOL_BALANCE;
+
+/// Seconds between balance checks while mining is paused.
+const PAUSE_INTERVAL: u64 = 10;
+
+/// Watches the fee payer balance between rounds.
+pub struct Watchdog {
+    /// Balance, in lamports, below which to warn and top up.
+    pub low_balance: u64,
+    pub top_up: Option<TopUp>,
+    notified: AtomicBool,
+}
+
+impl Watchdog {
+    pub fn new(low_balance: u64, top_up: Option<TopUp>) -> Self {
+        Self {
+            low_balance,
+            top_up,
+            notified: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Refills the fee payer from a funding keypair, up to a daily limit.
+pub struct TopUp {
+    pub keypair: Keypair,
+    pub amount: u64,
+    pub daily_limit: u64,
+    spent: Mutex<(NaiveDate, u64)>,
+}
+
+impl TopUp {
+    pub fn new(keypair: Keypair, amount: u64, daily_limit: u64) -> Self {
+        Self {
+            keypair,
+            amount,
+            daily_limit,
+            spent: Mutex::new((Utc::now().date_naive(), 0)),
+        }
+    }
+
+    /// Returns the amount that may still be sent today, up to the configured amount.
+    fn available(&self) -> u64 {
+        let mut spent = self.spent.lock().unwrap();
+        let today = Utc::now().date_naive();
+        if spent.0 != today {
+            *spent = (today, 0);
+        }
+        self.daily_limit.saturating_sub(spent.1).min(self.amount)
+    }
+
+    fn record(&self, amount: u64) {
+        self.spent.lock().unwrap().1 += amount;
+    }
+}
+
+impl Miner {
+    /// Waits until the fee payer can afford to submit, topping it up if configured.
+    /// Returns false if a shutdown is requested while paused.
+    pub async fn wait_for_balance(&self, watchdog: &Watchdog) -> bool {
+        let fee_payer = self.fee_payer().pubkey();
+        let min_balance = sol_to_lamports(MIN_SOL_BALANCE);
+        let mut paused = false;
+        loop {
+            // Leave errors to the next transaction if the balance cannot be read
+            let Ok(balance) = self.rpc_client.get_balance(&fee_payer).await else {
+                return true;
+            };
+            if balance > watchdog.low_balance {
+                watchdog.notified.store(false, Ordering::Relaxed);
+                if paused {
+                    println!(
+                        "{} Fee payer balance is {} SOL. Resuming mining.",
+                        "INFO".bold().cyan(),
+                        lamports_to_sol(balance)
+                    );
+                }
+                return true;
+            }
+
+            // Refill from the funding keypair, if configured
+            if let Some(top_up) = &watchdog.top_up {
+                if self.top_up(top_up, fee_payer).await {
+                    continue;
+                }
+            }
+
+            // Notify once per low balance episode
+            let affordable = balance > min_balance;
+            if !watchdog.notified.swap(true, Ordering::Relaxed) {
+                self.notifier.notify(
+                    NotifyEvent::BalanceLow,
+                    json!({
+                        "balance": balance,
+                        "fee_payer": fee_payer.to_string(),
+                        "paused": !affordable,
+                    }),
+                );
+            }
+
+            // Warn, but keep mining while a transaction is still affordable
+            if affordable {
+                println!(
+                    "{} Low fee payer balance: {} SOL. Please top up {}",
+                    "WARNING".bold().yellow(),
+                    lamports_to_sol(balance),
+                    fee_payer
+                );
+                return true;
+            }
+
+            // Pause until refilled
+            if !paused {
+                println!(
+                    "{} Insufficient balance: {} SOL. Mining is paused until {} holds more than {} SOL.",
+                    "WARNING".bold().yellow(),
+                    lamports_to_sol(balance),
+                    fee_payer,
+                    MIN_SOL_BALANCE
+                );
+                paused = true;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(PAUSE_INTERVAL)) => {}
+                _ = self.shutdown.wait() => return false,
+            }
+        }
+    }
+
+    /// Sends a top-up to the fee payer. Returns true if it landed.
+    async fn top_up(&self, top_up: &TopUp, fee_payer: Pubkey) -> bool {
+        let amount = top_up.available();
+        if amount == 0 {
+            return false;
+        }
+        let from = top_up.keypair.pubkey();
+        let Ok(blockhash) = self.rpc_client.get_latest_blockhash().await else {
+            return false;
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[transfer(&from, &fee_payer, amount)],
+            Some(&from),
+            &[&top_up.keypair],
+            blockhash,
+        );
+        match self.rpc_client.send_and_confirm_transaction(&tx).await {
+            Ok(sig) => {
+                top_up.record(amount);
+                println!(
+                    "{} Topped up fee payer with {} SOL from {} ({})",
+                    "INFO".bold().cyan(),
+                    lamports_to_sol(amount),
+                    from,
+                    sig
+                );
+                true
+            }
+            Err(err) => {
+                println!(
+                    "{} Failed to top up fee payer from {}: {}",
+                    "WARNING".bold().yellow(),
+                    from,
+                    err
+                );
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use b64::ToBase64;
+use drillx::Solution;
+use ore_api::{
+    consts::{BUS_ADDRESSES, CONFIG_ADDRESS, EPOCH_DURATION, ONE_MINUTE, TOLERANCE},
+    error::OreError,
+    event::MineEvent,
+    instruction::{Claim, Mine, OreInstruction},
+    state::{proof_pda, Bus, Config, Proof},
+};
+use ore_pool_api::{
+    instruction::{Attribute, PoolInstruction},
+    state::{member_pda, pool_pda, Member, Pool},
+};
+use solana_program::{
+    instruction::{CompiledInstruction, InstructionError},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    hash::{hashv, Hash},
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use steel::{Discriminator, Pod, Zeroable};
+
+/// Minimum difficulty served by the simulated config account.
+pub const MIN_DIFFICULTY: u64 = 8;
+
+/// Reward for a hash at the minimum difficulty, in grains.
+pub const BASE_REWARD_RATE: u64 = 1_000_000;
+
+/// Starting rewards held by each simulated bus, in grains.
+const BUS_REWARDS: u64 = 100_000_000_000;
+
+/// Lamports charged per transaction signature.
+const SIGNATURE_FEE: u64 = 5_000;
+
+/// Slots produced per second, used to derive the current slot from the clock.
+const SLOTS_PER_SECOND: i64 = 2;
+
+/// Compute units charged per instruction in simulation. The simulated chain does not meter
+/// compute, so every instruction costs the same nominal amount.
+const UNITS_PER_INSTRUCTION: u64 = 10_000;
+
+/// A transaction processed by the simulated chain.
+pub struct LandedTransaction {
+    pub slot: u64,
+    pub block_time: i64,
+    pub fee: u64,
+    pub result: Result<(), TransactionError>,
+    pub logs: Vec<String>,
+}
+
+/// In-memory ORE and pool program state for a single miner.
+pub struct Chain {
+    genesis: i64,
+    config: Config,
+    buses: Vec<Bus>,
+    proofs: HashMap<Pubkey, Proof>,
+    pools: HashMap<Pubkey, Pool>,
+    members: HashMap<Pubkey, Member>,
+    transactions: HashMap<Signature, LandedTransaction>,
+}
+
+impl Chain {
+    /// Creates a fresh chain with an open proof for `authority`.
+    pub fn new(authority: Pubkey) -> Self {
+        let now = now();
+        let buses = (0..BUS_ADDRESSES.len())
+            .map(|id| Bus {
+                id: id as u64,
+                rewards: BUS_REWARDS,
+                ..Bus::zeroed()
+            })
+            .collect();
+        let mut proofs = HashMap::new();
+        proofs.insert(
+            proof_pda(authority).0,
+            Proof {
+                authority,
+                balance: 0,
+                challenge: Hash::new_unique().to_bytes(),
+                last_hash: [0; 32],
+                last_hash_at: now,
+                last_claim_at: now,
+                miner: authority,
+                total_hashes: 0,
+                total_rewards: 0,
+            },
+        );
+        Self {
+            genesis: now,
+            config: Config {
+                base_reward_rate: BASE_REWARD_RATE,
+                last_reset_at: now,
+                min_difficulty: MIN_DIFFICULTY,
+                target_emmissions_rate: 0,
+            },
+            buses,
+            proofs,
+            pools: HashMap::new(),
+            members: HashMap::new(),
+            transactions: HashMap::new(),
+        }
+    }
+
+    /// Opens a pool operated by `authority` and returns its address.
+    pub fn launch_pool(&mut self, authority: Pubkey) -> Pubkey {
+        let address = pool_pda(authority).0;
+        self.pools.entry(address).or_insert_with(|| Pool {
+            authority,
+            ..Pool::zeroed()
+        });
+        address
+    }
+
+    /// Opens a member account for `authority` in the pool at `pool_address`, if needed.
+    pub fn join_pool(&mut self, authority: Pubkey, pool_address: Pubkey) {
+        let Some(pool) = self.pools.get_mut(&pool_address) else {
+            return;
+        };
+        let id = pool.total_members;
+        if let Entry::Vacant(entry) = self.members.entry(member_pda(authority, pool_address).0) {
+            pool.total_members += 1;
+            entry.insert(Member {
+                id,
+                pool: pool_address,
+                authority,
+                balance: 0,
+                total_balance: 0,
+            });
+        }
+    }
+
+    pub fn slot(&self) -> u64 {
+        (now() - self.genesis).saturating_mul(SLOTS_PER_SECOND) as u64
+    }
+
+    pub fn clock(&self) -> Clock {
+        Clock {
+            slot: self.slot(),
+            epoch_start_timestamp: self.genesis,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp: now(),
+        }
+    }
+
+    /// Returns the account at `address`, if the simulation serves it.
+    pub fn account(&self, address: &Pubkey) -> Option<Account> {
+        let (owner, data) = if *address == CONFIG_ADDRESS {
+            (ore_api::ID, account_data(&self.config))
+        } else if let Some(i) = BUS_ADDRESSES.iter().position(|bus| bus == address) {
+            (ore_api::ID, account_data(&self.buses[i]))
+        } else if let Some(proof) = self.proofs.get(address) {
+            (ore_api::ID, account_data(proof))
+        } else if let Some(pool) = self.pools.get(address) {
+            (ore_pool_api::ID, account_data(pool))
+        } else if let Some(member) = self.members.get(address) {
+            (ore_pool_api::ID, account_data(member))
+        } else if *address == sysvar::clock::ID {
+            (sysvar::ID, bincode::serialize(&self.clock()).ok()?)
+        } else {
+            return None;
+        };
+        Some(Account {
+            lamports: 1_000_000_000,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        })
+    }
+
+    pub fn transaction(&self, signature: &Signature) -> Option<&LandedTransaction> {
+        self.transactions.get(signature)
+    }
+
+    /// Executes the ORE and pool instructions in `tx` and records the outcome.
+    /// Instructions for other programs are accepted without effect.
+    pub fn process(&mut self, tx: &Transaction) -> Signature {
+        let signature = tx.signatures[0];
+        let mut logs = vec![];
+        let mut result = Ok(());
+        let keys = &tx.message.account_keys;
+        for (i, ix) in tx.message.instructions.iter().enumerate() {
+            let program_id = keys[ix.program_id_index as usize];
+            logs.push(format!("Program {} invoke [1]", program_id));
+            if ix.data.is_empty() {
+                continue;
+            }
+            let outcome = if program_id == ore_api::ID {
+                self.execute(keys, ix, &mut logs)
+                    .map_err(|err| (InstructionError::Custom(err as u32), err.to_string()))
+            } else if program_id == ore_pool_api::ID {
+                self.execute_pool(keys, ix).map_err(|err| {
+                    (
+                        InstructionError::from(u64::from(err.clone())),
+                        err.to_string(),
+                    )
+                })
+            } else {
+                continue;
+            };
+            if let Err((err, message)) = outcome {
+                logs.push(format!("Program {} failed: {}", program_id, message));
+                result = Err(TransactionError::InstructionError(i as u8, err));
+                break;
+            }
+        }
+        self.transactions.insert(
+            signature,
+            LandedTransaction {
+                slot: self.slot(),
+                block_time: now(),
+                fee: SIGNATURE_FEE * tx.signatures.len() as u64,
+                result,
+                logs,
+            },
+        );
+        signature
+    }
+
+    fn execute(
+        &mut self,
+        keys: &[Pubkey],
+        ix: &CompiledInstruction,
+        logs: &mut Vec<String>,
+    ) -> Result<(), OreError> {
+        match OreInstruction::try_from(ix.data[0]) {
+            Ok(OreInstruction::Mine) => {
+                let proof_address = keys[ix.accounts[3] as usize];
+                let bus_address = keys[ix.accounts[1] as usize];
+                Mine::try_from_bytes(&ix.data[1..])
+                    .map_err(|_| OreError::HashInvalid)
+                    .and_then(|args| {
+                        self.mine(
+                            proof_address,
+                            bus_address,
+                            Solution::new(args.digest, args.nonce),
+                        )
+                    })
+                    .map(|event| {
+                        logs.push(format!(
+                            "Program return: {} {}",
+                            ore_api::ID,
+                            bytemuck::bytes_of(&event).to_base64(b64::STANDARD)
+                        ));
+                    })
+            }
+            Ok(OreInstruction::Claim) => {
+                let proof_address = keys[ix.accounts[2] as usize];
+                Claim::try_from_bytes(&ix.data[1..])
+                    .map_err(|_| OreError::ClaimTooLarge)
+                    .and_then(|args| self.claim(proof_address, u64::from_le_bytes(args.amount)))
+            }
+            Ok(OreInstruction::Reset) => {
+                self.reset();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn execute_pool(
+        &mut self,
+        keys: &[Pubkey],
+        ix: &CompiledInstruction,
+    ) -> Result<(), ProgramError> {
+        match PoolInstruction::try_from(ix.data[0]) {
+            Ok(PoolInstruction::Attribute) => {
+                let member_address = keys[ix.accounts[4] as usize];
+                let args = Attribute::try_from_bytes(&ix.data[1..])?;
+                self.attribute(member_address, u64::from_le_bytes(args.total_balance))
+            }
+            Ok(PoolInstruction::Claim) => {
+                let member_address = keys[ix.accounts[2] as usize];
+                let args = ore_pool_api::instruction::Claim::try_from_bytes(&ix.data[1..])?;
+                self.claim_from_pool(member_address, u64::from_le_bytes(args.amount))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Executes `tx` against a copy of the chain, leaving it untouched. Returns the outcome,
+    /// logs and compute units consumed.
+    pub fn simulate(&self, tx: &Transaction) -> (Result<(), TransactionError>, Vec<String>, u64) {
+        let mut scratch = Chain {
+            genesis: self.genesis,
+            config: self.config,
+            buses: self.buses.clone(),
+            proofs: self.proofs.clone(),
+            pools: self.pools.clone(),
+            members: self.members.clone(),
+            transactions: HashMap::new(),
+        };
+        let signature = scratch.process(tx);
+        let landed = scratch
+            .transactions
+            .remove(&signature)
+            .expect("Processed transaction is recorded");
+        let units_consumed = UNITS_PER_INSTRUCTION * tx.message.instructions.len() as u64;
+        (landed.result, landed.logs, units_consumed)
+    }
+
+    fn mine(
+        &mut self,
+        proof_address: Pubkey,
+        bus_address: Pubkey,
+        solution: Solution,
+    ) -> Result<MineEvent, OreError> {
+        let t = now();
+
+        // Check epoch
+        if self.config.last_reset_at.saturating_add(EPOCH_DURATION) <= t {
+            return Err(OreError::NeedsReset);
+        }
+
+        // Check accounts
+        let bus_index = BUS_ADDRESSES
+            .iter()
+            .position(|bus| *bus == bus_address)
+            .ok_or(OreError::AuthFailed)?;
+        let proof = self
+            .proofs
+            .get_mut(&proof_address)
+            .ok_or(OreError::AuthFailed)?;
+
+        // Reject spam
+        let t_target = proof.last_hash_at.saturating_add(ONE_MINUTE);
+        if t < t_target.saturating_sub(TOLERANCE) {
+            return Err(OreError::Spam);
+        }
+
+        // Validate solution
+        if !solution.is_valid(&proof.challenge) {
+            return Err(OreError::HashInvalid);
+        }
+        let hash = solution.to_hash();
+        let difficulty = hash.difficulty() as u64;
+        if difficulty < self.config.min_difficulty {
+            return Err(OreError::HashTooEasy);
+        }
+
+        // Pay reward from bus
+        let timing = t.saturating_sub(t_target);
+        let bus = &mut self.buses[bus_index];
+        let reward = reward(difficulty, self.config.min_difficulty, timing).min(bus.rewards);
+        bus.rewards -= reward;
+        bus.theoretical_rewards += reward;
+
+        // Update proof
+        proof.balance += reward;
+        proof.challenge = hashv(&[&proof.challenge, &hash.h]).to_bytes();
+        proof.last_hash = hash.h;
+        proof.last_hash_at = t;
+        proof.total_hashes += 1;
+        proof.total_rewards += reward;
+        Ok(MineEvent {
+            balance: proof.balance,
+            difficulty,
+            last_hash_at: proof.last_hash_at,
+            timing,
+            net_reward: reward,
+            net_base_reward: reward,
+            net_miner_boost_reward: 0,
+            net_staker_boost_reward: 0,
+        })
+    }
+
+    fn claim(&mut self, proof_address: Pubkey, amount: u64) -> Result<(), OreError> {
+        let proof = self
+            .proofs
+            .get_mut(&proof_address)
+            .ok_or(OreError::AuthFailed)?;
+        proof.balance = proof
+            .balance
+            .checked_sub(amount)
+            .ok_or(OreError::ClaimTooLarge)?;
+        Ok(())
+    }
+
+    /// Credits a member with the rewards it has earned since its last attribution.
+    fn attribute(
+        &mut self,
+        member_address: Pubkey,
+        total_balance: u64,
+    ) -> Result<(), ProgramError> {
+        let member = self
+            .members
+            .get_mut(&member_address)
+            .ok_or(ProgramError::UninitializedAccount)?;
+        let earned = total_balance
+            .checked_sub(member.total_balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        member.balance += earned;
+        member.total_balance = total_balance;
+        Ok(())
+    }
+
+    fn claim_from_pool(&mut self, member_address: Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let member = self
+            .members
+            .get_mut(&member_address)
+            .ok_or(ProgramError::UninitializedAccount)?;
+        member.balance = member
+            .balance
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.config.last_reset_at = now();
+        for bus in self.buses.iter_mut() {
+            bus.rewards = BUS_REWARDS;
+            bus.theoretical_rewards = 0;
+        }
+    }
+}
+
+/// The reward for a hash, following the program's difficulty scaling and liveness penalty.
+pub fn reward(difficulty: u64, min_difficulty: u64, timing: i64) -> u64 {
+    let normalized = difficulty.saturating_sub(min_difficulty).min(63) as u32;
+    let mut reward = BASE_REWARD_RATE.saturating_mul(2u64.saturating_pow(normalized));
+    if timing > TOLERANCE {
+        let tardiness = timing as u64;
+        let halvings = tardiness / ONE_MINUTE as u64;
+        reward = reward.checked_shr(halvings as u32).unwrap_or(0);
+        let remainder = tardiness - halvings * ONE_MINUTE as u64;
+        reward -= (reward / 2).saturating_mul(remainder) / ONE_MINUTE as u64;
+    }
+    reward
+}
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn account_data<T: Discriminator + Pod>(account: &T) -> Vec<u8> {
+    let mut data = vec![0u8; 8];
+    data[0] = T::discriminator();
+    data.extend_from_slice(bytemuck::bytes_of(account));
+    data
+}
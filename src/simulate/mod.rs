@@ -0,0 +1,7 @@
+mod chain;
+mod pool;
+mod rpc;
+
+pub use chain::*;
+pub use pool::*;
+pub use rpc::*;
@@ -0,0 +1,358 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use drillx::Solution;
+use ore_api::consts::ONE_MINUTE;
+use ore_pool_types::{
+    BalanceUpdate, Challenge, ContributePayload, Member, MemberChallenge, PoolAddress,
+    PoolMemberMiningEvent, UpdateBalancePayload,
+};
+use rand::Rng;
+use reqwest::StatusCode;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{hash::Hash, signature::Signature};
+use tokio::sync::{mpsc, watch};
+
+use super::{now, reward, Chain, MIN_DIFFICULTY};
+use crate::pool_client::{PoolClient, PoolError, PoolFeed, PoolProtocol};
+
+/// Seconds after the end of a round before the pool's transaction lands.
+const LANDING_DELAY: i64 = 2;
+
+/// Seconds before the end of a round the pool stops accepting contributions.
+const CUTOFF_BUFFER: i64 = 5;
+
+/// A single pool round, as seen by the simulated operator.
+struct PoolState {
+    address: Pubkey,
+    member: Option<Member>,
+    challenge: [u8; 32],
+    last_hash_at: i64,
+    best_contribution: u64,
+    balance: u64,
+    event: Option<PoolMemberMiningEvent>,
+    challenges: watch::Sender<Option<MemberChallenge>>,
+    subscribers: Vec<mpsc::UnboundedSender<PoolMemberMiningEvent>>,
+}
+
+impl PoolState {
+    /// Lands the current round, if it has ended, and opens the next one.
+    fn land(&mut self) {
+        let t = now();
+        let t_target = self.last_hash_at + ONE_MINUTE;
+        if t < t_target + LANDING_DELAY {
+            return;
+        }
+
+        // Other members contribute a hash near the minimum difficulty
+        let others = MIN_DIFFICULTY + rand::thread_rng().gen_range(0..4);
+        let member_difficulty = self.best_contribution;
+        let difficulty = others.max(member_difficulty);
+        let timing = t - t_target;
+        let net_reward = reward(difficulty, MIN_DIFFICULTY, timing);
+
+        // Split reward by share of total work
+        let member_reward = if member_difficulty > 0 {
+            let member_work = 1u128 << member_difficulty;
+            let total_work = member_work + (1u128 << others);
+            (net_reward as u128 * member_work / total_work) as u64
+        } else {
+            0
+        };
+        self.balance += net_reward;
+        if let Some(member) = self.member.as_mut() {
+            member.total_balance += member_reward as i64;
+        }
+        let signature = Signature::new_unique();
+        let (balance, last_hash_at) = (self.balance, self.last_hash_at);
+        let event = || PoolMemberMiningEvent {
+            signature,
+            block: (t as u64).saturating_mul(2),
+            timestamp: t as u64,
+            balance,
+            difficulty,
+            last_hash_at,
+            timing,
+            net_reward,
+            net_base_reward: net_reward,
+            net_miner_boost_reward: 0,
+            net_staker_boost_reward: 0,
+            member_difficulty,
+            member_reward,
+        };
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event()).is_ok());
+        self.event = Some(event());
+
+        // Start next round
+        self.challenge = Hash::new_unique().to_bytes();
+        self.last_hash_at = t;
+        self.best_contribution = 0;
+        self.challenges.send_replace(Some(self.challenge()));
+    }
+
+    fn register(&mut self, authority: Pubkey) -> Member {
+        let address = self.address;
+        self.member
+            .get_or_insert_with(|| Member {
+                address: ore_pool_api::state::member_pda(authority, address)
+                    .0
+                    .to_string(),
+                id: 0,
+                authority: authority.to_string(),
+                pool_address: address.to_string(),
+                total_balance: 0,
+                is_approved: true,
+                is_kyc: false,
+                is_synced: true,
+            })
+            .clone()
+    }
+
+    fn challenge(&self) -> MemberChallenge {
+        #[allow(deprecated)]
+        MemberChallenge {
+            challenge: Challenge {
+                challenge: self.challenge,
+                lash_hash_at: self.last_hash_at,
+                min_difficulty: MIN_DIFFICULTY,
+                cutoff_time: (self.last_hash_at + ONE_MINUTE - CUTOFF_BUFFER) as u64,
+            },
+            num_total_members: 1,
+            device_id: 0,
+            num_devices: 1,
+            unix_timestamp: now(),
+        }
+    }
+
+    fn contribute(&mut self, solution: &Solution) -> bool {
+        if now() > self.last_hash_at + ONE_MINUTE || !solution.is_valid(&self.challenge) {
+            return false;
+        }
+        let difficulty = solution.to_hash().difficulty() as u64;
+        self.best_contribution = self.best_contribution.max(difficulty);
+        true
+    }
+}
+
+/// An in-memory pool operator with a single member, landing a round every minute. Serves
+/// the pool protocol directly as a [`PoolClient`], and keeps the member's account on the
+/// simulated chain.
+#[derive(Clone)]
+pub struct SimulatedPool {
+    url: String,
+    state: Arc<Mutex<PoolState>>,
+    chain: Arc<Mutex<Chain>>,
+}
+
+impl SimulatedPool {
+    pub fn new(url: String, chain: Arc<Mutex<Chain>>) -> Self {
+        let (challenges, _) = watch::channel(None);
+        let address = chain.lock().unwrap().launch_pool(Pubkey::new_unique());
+        let state = Arc::new(Mutex::new(PoolState {
+            address,
+            member: None,
+            challenge: Hash::new_unique().to_bytes(),
+            last_hash_at: now(),
+            best_contribution: 0,
+            balance: 0,
+            event: None,
+            challenges,
+            subscribers: vec![],
+        }));
+        {
+            let state = state.lock().unwrap();
+            state.challenges.send_replace(Some(state.challenge()));
+        }
+
+        // Land rounds in the background
+        tokio::spawn({
+            let state = state.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    state.lock().unwrap().land();
+                }
+            }
+        });
+        Self { url, state, chain }
+    }
+
+    /// Moves the current round `seconds` closer to its end, landing it if it is over.
+    #[cfg(test)]
+    pub fn fast_forward(&self, seconds: i64) {
+        let mut state = self.state.lock().unwrap();
+        state.last_hash_at -= seconds;
+        state.challenges.send_replace(Some(state.challenge()));
+        state.land();
+    }
+}
+
+#[async_trait]
+impl PoolClient for SimulatedPool {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    async fn protocol(&self) -> Result<PoolProtocol, PoolError> {
+        Ok(PoolProtocol::Current)
+    }
+
+    async fn address(&self) -> Result<PoolAddress, PoolError> {
+        Ok(PoolAddress {
+            address: self.state.lock().unwrap().address,
+            bump: 255,
+        })
+    }
+
+    async fn register(&self, authority: Pubkey) -> Result<Member, PoolError> {
+        let mut state = self.state.lock().unwrap();
+        self.chain
+            .lock()
+            .unwrap()
+            .join_pool(authority, state.address);
+        Ok(state.register(authority))
+    }
+
+    async fn member(&self, _authority: Pubkey) -> Result<Member, PoolError> {
+        self.state
+            .lock()
+            .unwrap()
+            .member
+            .clone()
+            .ok_or(status(StatusCode::NOT_FOUND))
+    }
+
+    async fn challenge(&self, _authority: Pubkey) -> Result<MemberChallenge, PoolError> {
+        Ok(self.state.lock().unwrap().challenge())
+    }
+
+    async fn contribute(&self, payload: &ContributePayload) -> Result<(), PoolError> {
+        if !self.state.lock().unwrap().contribute(&payload.solution) {
+            return Err(status(StatusCode::BAD_REQUEST));
+        }
+        Ok(())
+    }
+
+    async fn latest_event(&self, _authority: Pubkey) -> Result<PoolMemberMiningEvent, PoolError> {
+        let state = self.state.lock().unwrap();
+        let event = state.event.as_ref().ok_or(status(StatusCode::NOT_FOUND))?;
+        Ok(PoolMemberMiningEvent {
+            signature: event.signature,
+            block: event.block,
+            timestamp: event.timestamp,
+            balance: event.balance,
+            difficulty: event.difficulty,
+            last_hash_at: event.last_hash_at,
+            timing: event.timing,
+            net_reward: event.net_reward,
+            net_base_reward: event.net_base_reward,
+            net_miner_boost_reward: event.net_miner_boost_reward,
+            net_staker_boost_reward: event.net_staker_boost_reward,
+            member_difficulty: event.member_difficulty,
+            member_reward: event.member_reward,
+        })
+    }
+
+    async fn commit(&self, payload: &UpdateBalancePayload) -> Result<BalanceUpdate, PoolError> {
+        let state = self.state.lock().unwrap();
+        let member = state.member.as_ref().ok_or(status(StatusCode::NOT_FOUND))?;
+
+        // Submit the member's attribution as the operator
+        let mut chain = self.chain.lock().unwrap();
+        let signature = chain.process(&payload.transaction);
+        if let Some(Err(err)) = chain.transaction(&signature).map(|tx| tx.result.clone()) {
+            return Err(PoolError::Status {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message: err.to_string(),
+            });
+        }
+        Ok(BalanceUpdate {
+            balance: member.total_balance.max(0) as u64,
+            signature,
+        })
+    }
+
+    async fn subscribe(&self, _authority: Pubkey) -> Option<PoolFeed> {
+        let mut state = self.state.lock().unwrap();
+        let mut challenges = state.challenges.subscribe();
+        challenges.mark_changed();
+        let (event_tx, events) = mpsc::unbounded_channel();
+        state.subscribers.push(event_tx);
+        Some(PoolFeed::new(self.url.clone(), challenges, events))
+    }
+}
+
+fn status(status: StatusCode) -> PoolError {
+    PoolError::Status {
+        status,
+        message: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mines_a_round_through_the_client() {
+        let authority = Pubkey::new_unique();
+        let chain = Arc::new(Mutex::new(Chain::new(authority)));
+        let simulated = SimulatedPool::new("simulated".to_string(), chain);
+        let pool: Arc<dyn PoolClient> = Arc::new(simulated.clone());
+
+        // Register
+        let member = pool.register(authority).await.unwrap();
+        assert_eq!(member.authority, authority.to_string());
+        assert_eq!(pool.member(authority).await.unwrap().id, member.id);
+
+        // Contribute a solution to the current challenge
+        let challenge = pool.challenge(authority).await.unwrap();
+        let (nonce, hash) = (0u64..)
+            .find_map(|nonce| {
+                drillx::hash(&challenge.challenge.challenge, &nonce.to_le_bytes())
+                    .ok()
+                    .map(|hash| (nonce, hash))
+            })
+            .unwrap();
+        pool.contribute(&ContributePayload {
+            authority,
+            solution: Solution::new(hash.d, nonce.to_le_bytes()),
+            signature: Signature::default(),
+        })
+        .await
+        .unwrap();
+        assert!(pool.latest_event(authority).await.is_err());
+
+        // Land the round without waiting out the minute
+        let mut feed = pool.subscribe(authority).await.unwrap();
+        {
+            let mut state = simulated.state.lock().unwrap();
+            state.last_hash_at -= ONE_MINUTE + LANDING_DELAY;
+            state.land();
+        }
+
+        // Read the event, polled and pushed
+        let last_hash_at = simulated
+            .state
+            .lock()
+            .unwrap()
+            .event
+            .as_ref()
+            .unwrap()
+            .last_hash_at;
+        let event = pool.latest_event(authority).await.unwrap();
+        assert_eq!(event.last_hash_at, last_hash_at);
+        assert_eq!(event.member_difficulty, hash.difficulty() as u64);
+        let pushed = feed
+            .next_event(last_hash_at, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(pushed.signature, event.signature);
+        let next = feed.next_challenge(last_hash_at).await.unwrap();
+        assert_ne!(next.challenge.challenge, challenge.challenge.challenge);
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use b64::FromBase64;
+use serde_json::{json, Value};
+use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_request::RpcRequest,
+    rpc_response::{Response, RpcBlockhash, RpcResponseContext, RpcSimulateTransactionResult},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{hash::Hash, signature::Signature, transaction::Transaction};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
+    EncodedTransactionWithStatusMeta, TransactionBinaryEncoding, TransactionConfirmationStatus,
+    TransactionStatus, TransactionStatusMeta, UiTransactionStatusMeta,
+};
+
+use super::Chain;
+
+/// Lamports reported for every balance query.
+const BALANCE: u64 = 1_000_000_000;
+
+/// An RPC transport that answers requests from the simulated chain.
+pub struct SimulatedRpc {
+    chain: Arc<Mutex<Chain>>,
+}
+
+impl SimulatedRpc {
+    pub fn new(chain: Arc<Mutex<Chain>>) -> Self {
+        Self { chain }
+    }
+
+    fn handle(&self, request: RpcRequest, params: &Value) -> Result<Value, String> {
+        let mut chain = self.chain.lock().unwrap();
+        let context = RpcResponseContext {
+            slot: chain.slot(),
+            api_version: None,
+        };
+        let value = match request {
+            RpcRequest::GetAccountInfo => {
+                let address = pubkey_param(&params[0])?;
+                let account = chain.account(&address).map(|account| {
+                    encode_ui_account(&address, &account, UiAccountEncoding::Base64, None, None)
+                });
+                to_value(Response {
+                    context,
+                    value: account,
+                })?
+            }
+            RpcRequest::GetMultipleAccounts => {
+                let accounts = params[0]
+                    .as_array()
+                    .ok_or("Missing addresses")?
+                    .iter()
+                    .map(|address| {
+                        let address = pubkey_param(address)?;
+                        Ok(chain.account(&address).map(|account| {
+                            encode_ui_account(
+                                &address,
+                                &account,
+                                UiAccountEncoding::Base64,
+                                None,
+                                None,
+                            )
+                        }))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                to_value(Response {
+                    context,
+                    value: accounts,
+                })?
+            }
+            RpcRequest::GetBalance => to_value(Response {
+                context,
+                value: BALANCE,
+            })?,
+            RpcRequest::GetLatestBlockhash => to_value(Response {
+                context,
+                value: RpcBlockhash {
+                    blockhash: Hash::new_unique().to_string(),
+                    last_valid_block_height: chain.slot() + 150,
+                },
+            })?,
+            RpcRequest::GetSlot => json!(chain.slot()),
+            RpcRequest::GetVersion => json!({ "solana-core": "2.1.13", "feature-set": 0 }),
+            RpcRequest::SendTransaction => {
+                let tx = transaction_param(&params[0])?;
+                json!(chain.process(&tx).to_string())
+            }
+            RpcRequest::SimulateTransaction => {
+                let tx = transaction_param(&params[0])?;
+                let (result, logs, units_consumed) = chain.simulate(&tx);
+                to_value(Response {
+                    context,
+                    value: RpcSimulateTransactionResult {
+                        err: result.err(),
+                        logs: Some(logs),
+                        accounts: None,
+                        units_consumed: Some(units_consumed),
+                        return_data: None,
+                        inner_instructions: None,
+                        replacement_blockhash: None,
+                    },
+                })?
+            }
+            RpcRequest::GetSignatureStatuses => {
+                let statuses = params[0]
+                    .as_array()
+                    .ok_or("Missing signatures")?
+                    .iter()
+                    .map(|signature| {
+                        let signature = signature_param(signature)?;
+                        Ok(chain.transaction(&signature).map(|tx| TransactionStatus {
+                            slot: tx.slot,
+                            confirmations: None,
+                            status: tx.result.clone(),
+                            err: tx.result.clone().err(),
+                            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+                        }))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                to_value(Response {
+                    context,
+                    value: statuses,
+                })?
+            }
+            RpcRequest::GetTransaction => {
+                let signature = signature_param(&params[0])?;
+                match chain.transaction(&signature) {
+                    None => Value::Null,
+                    Some(tx) => {
+                        let meta = TransactionStatusMeta {
+                            status: tx.result.clone(),
+                            fee: tx.fee,
+                            log_messages: Some(tx.logs.clone()),
+                            ..Default::default()
+                        };
+                        to_value(EncodedConfirmedTransactionWithStatusMeta {
+                            slot: tx.slot,
+                            transaction: EncodedTransactionWithStatusMeta {
+                                transaction: EncodedTransaction::Binary(
+                                    String::new(),
+                                    TransactionBinaryEncoding::Base64,
+                                ),
+                                meta: Some(UiTransactionStatusMeta::from(meta)),
+                                version: None,
+                            },
+                            block_time: Some(tx.block_time),
+                        })?
+                    }
+                }
+            }
+            request => return Err(format!("{} is not supported in simulation", request)),
+        };
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl RpcSender for SimulatedRpc {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        self.handle(request, &params).map_err(|err| ClientError {
+            request: Some(request),
+            kind: ClientErrorKind::Custom(err),
+        })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "simulated".to_string()
+    }
+}
+
+fn to_value(value: impl serde::Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|err| err.to_string())
+}
+
+fn pubkey_param(value: &Value) -> Result<Pubkey, String> {
+    value
+        .as_str()
+        .ok_or("Missing address")?
+        .parse()
+        .map_err(|_| "Invalid address".to_string())
+}
+
+fn transaction_param(value: &Value) -> Result<Transaction, String> {
+    let data = value
+        .as_str()
+        .ok_or("Missing transaction")?
+        .from_base64()
+        .map_err(|err| err.to_string())?;
+    bincode::deserialize(&data).map_err(|err| err.to_string())
+}
+
+fn signature_param(value: &Value) -> Result<Signature, String> {
+    value
+        .as_str()
+        .ok_or("Missing signature")?
+        .parse()
+        .map_err(|_| "Invalid signature".to_string())
+}
@@ -0,0 +1,68 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+/// Number of recent rounds to derive the buffer from.
+const WINDOW: usize = 20;
+
+/// Rounds to observe before the measured buffer replaces the configured one.
+const MIN_SAMPLES: usize = 3;
+
+/// Percentile of observed delays the buffer should cover.
+const PERCENTILE: f64 = 0.9;
+
+/// Upper bound on the buffer, so a few pathological rounds cannot stall mining.
+const MAX_BUFFER: u64 = 30;
+
+/// Tracks how long mine transactions take to land and picks a buffer time from it.
+#[derive(Default)]
+pub struct AdaptiveBuffer {
+    send_latencies: Mutex<VecDeque<f64>>,
+    landing_delays: Mutex<VecDeque<f64>>,
+}
+
+impl AdaptiveBuffer {
+    /// Records the time from submitting a transaction to its confirmation.
+    pub fn record_send(&self, elapsed: Duration) {
+        push(&self.send_latencies, elapsed.as_secs_f64());
+    }
+
+    /// Records when a round landed relative to the end of its window. `timing` is the
+    /// on-chain `MineEvent.timing` and `buffer` is the buffer time the round was mined with,
+    /// so their sum is the buffer that would have landed the round exactly on time.
+    pub fn record_landing(&self, timing: i64, buffer: u64) {
+        push(
+            &self.landing_delays,
+            timing.saturating_add(buffer as i64).max(0) as f64,
+        );
+    }
+
+    /// Returns the buffer time for the next round, or `fallback` until enough rounds
+    /// have been observed.
+    pub fn buffer(&self, fallback: u64) -> u64 {
+        let send_latency = percentile(&self.send_latencies);
+        let landing_delay = percentile(&self.landing_delays);
+        match (send_latency, landing_delay) {
+            (None, None) => fallback,
+            (a, b) => a.unwrap_or(0f64).max(b.unwrap_or(0f64)).ceil() as u64,
+        }
+        .min(MAX_BUFFER)
+    }
+}
+
+fn push(samples: &Mutex<VecDeque<f64>>, value: f64) {
+    let mut samples = samples.lock().unwrap();
+    samples.push_back(value);
+    while samples.len() > WINDOW {
+        samples.pop_front();
+    }
+}
+
+fn percentile(samples: &Mutex<VecDeque<f64>>) -> Option<f64> {
+    let samples = samples.lock().unwrap();
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let index = ((sorted.len() - 1) as f64 * PERCENTILE).round() as usize;
+    Some(sorted[index])
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeSet;
+
+use colored::*;
+use core_affinity::CoreId;
+
+use crate::error::Error;
+
+/// Resolves a `--cores` value into one entry per worker thread.
+///
+/// The value is either `ALL`, a thread count such as `8`, or a list of core ids and
+/// ranges such as `0-7,16-23`. Workers are pinned to their core unless `no_pin` is set,
+/// in which case a count may also exceed the number of available cores.
+pub fn select_cores(
+    spec: &str,
+    skip_smt: bool,
+    no_pin: bool,
+) -> Result<Vec<Option<CoreId>>, Error> {
+    let mut available: Vec<usize> = core_affinity::get_core_ids()
+        .ok_or(Error::InvalidCores("Failed to fetch core ids".to_string()))?
+        .into_iter()
+        .map(|core| core.id)
+        .collect();
+    available.sort_unstable();
+
+    // Skip hyperthreads
+    if skip_smt {
+        match smt_siblings() {
+            Some(siblings) => available.retain(|id| !siblings.contains(id)),
+            None => println!(
+                "{} SMT topology is unavailable on this platform. Using all cores.",
+                "WARNING".bold().yellow()
+            ),
+        }
+    }
+
+    // Parse selection
+    let spec = spec.trim();
+    let ids = if spec.eq_ignore_ascii_case("ALL") {
+        available.clone()
+    } else if let Ok(count) = spec.parse::<usize>() {
+        if count == 0 {
+            return Err(Error::InvalidCores(
+                "Core count must be at least 1".to_string(),
+            ));
+        }
+        if no_pin {
+            return Ok(vec![None; count]);
+        }
+        if count > available.len() {
+            return Err(Error::InvalidCores(format!(
+                "Cannot exceed available cores ({}). Use --no-pin to run more threads than cores.",
+                available.len()
+            )));
+        }
+        available[..count].to_vec()
+    } else {
+        let ids = parse_core_list(spec)?;
+        if let Some(id) = ids.iter().find(|id| !available.contains(id)) {
+            return Err(Error::InvalidCores(format!("Core {} is not available", id)));
+        }
+        ids
+    };
+
+    Ok(ids
+        .into_iter()
+        .map(|id| if no_pin { None } else { Some(CoreId { id }) })
+        .collect())
+}
+
+/// Parses a comma-separated list of core ids and inclusive ranges, e.g. `0-3,8,10-11`.
+fn parse_core_list(spec: &str) -> Result<Vec<usize>, Error> {
+    let invalid = || Error::InvalidCores(format!("Invalid core list: {}", spec));
+    let mut ids = BTreeSet::new();
+    for part in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let start = start.trim().parse::<usize>().map_err(|_| invalid())?;
+                let end = end.trim().parse::<usize>().map_err(|_| invalid())?;
+                if start > end {
+                    return Err(invalid());
+                }
+                ids.extend(start..=end);
+            }
+            None => {
+                ids.insert(part.parse::<usize>().map_err(|_| invalid())?);
+            }
+        }
+    }
+    if ids.is_empty() {
+        return Err(invalid());
+    }
+    Ok(ids.into_iter().collect())
+}
+
+/// Returns every logical core that is not the first thread of its physical core.
+#[cfg(target_os = "linux")]
+fn smt_siblings() -> Option<BTreeSet<usize>> {
+    let mut siblings = BTreeSet::new();
+    for core in core_affinity::get_core_ids()? {
+        let path = format!(
+            "/sys/devices/system/cpu/cpu{}/topology/thread_siblings_list",
+            core.id
+        );
+        let list = std::fs::read_to_string(path).ok()?;
+        let threads = parse_core_list(list.trim()).ok()?;
+        siblings.extend(threads.into_iter().skip(1));
+    }
+    Some(siblings)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn smt_siblings() -> Option<BTreeSet<usize>> {
+    None
+}
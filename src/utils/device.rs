@@ -0,0 +1,134 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use fs2::FileExt;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::Error;
+
+/// Seconds after which a slot claimed by another host is considered abandoned.
+const REMOTE_LEASE_SECS: i64 = 300;
+
+/// The device id used to split a member's nonce space between processes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceId {
+    /// Claim a free slot through a lock file shared by processes on this host.
+    Auto,
+    Fixed(u64),
+}
+
+impl FromStr for DeviceId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(DeviceId::Auto);
+        }
+        s.parse::<u64>()
+            .map(DeviceId::Fixed)
+            .map_err(|_| format!("expected a number or \"auto\", got \"{}\"", s))
+    }
+}
+
+/// An exclusively held device slot. The lock is released when this is dropped or the
+/// process exits, however it exits.
+///
+/// Locks only exclude processes on the same host. When the lock directory is shared
+/// between hosts, each holder also records its hostname and a heartbeat, and slots with a
+/// live heartbeat from another host are skipped.
+pub struct DeviceLock {
+    pub id: u64,
+    file: File,
+}
+
+impl DeviceLock {
+    /// Claims the lowest free device slot below `num_devices` for `authority`.
+    pub fn acquire(dir: PathBuf, authority: Pubkey, num_devices: u64) -> Result<Self, Error> {
+        fs::create_dir_all(&dir).map_err(|err| {
+            Error::Internal(format!("failed to create {}: {}", dir.display(), err))
+        })?;
+        for id in 0..num_devices {
+            let path = dir.join(format!("{}-{}.lock", authority, id));
+            let Ok(mut file) = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+            else {
+                continue;
+            };
+            if file.try_lock_exclusive().is_err() {
+                continue;
+            }
+            if held_by_remote_host(&mut file) {
+                let _ = file.unlock();
+                continue;
+            }
+            let mut lock = Self { id, file };
+            lock.heartbeat();
+            return Ok(lock);
+        }
+        Err(Error::TooManyDevices)
+    }
+
+    /// Refreshes the holder record so other hosts sharing the lock directory keep
+    /// skipping this slot.
+    pub fn heartbeat(&mut self) {
+        let record = format!(
+            "{} {} {}\n",
+            hostname(),
+            std::process::id(),
+            chrono::Utc::now().timestamp()
+        );
+        let _ = self.file.set_len(0);
+        let _ = self.file.seek(SeekFrom::Start(0));
+        let _ = self.file.write_all(record.as_bytes());
+    }
+}
+
+impl Drop for DeviceLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// The default directory for device lock files, shared by all processes on this host.
+pub fn default_device_lock_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ore")
+        .join("devices")
+}
+
+/// Whether the slot's last holder was another host whose heartbeat is still fresh.
+fn held_by_remote_host(file: &mut File) -> bool {
+    let mut record = String::new();
+    if file.read_to_string(&mut record).is_err() {
+        return false;
+    }
+    let mut fields = record.split_whitespace();
+    let (Some(host), Some(_pid), Some(heartbeat)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return false;
+    };
+    let Ok(heartbeat) = heartbeat.parse::<i64>() else {
+        return false;
+    };
+    host != hostname() && chrono::Utc::now().timestamp() - heartbeat < REMOTE_LEASE_SECS
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
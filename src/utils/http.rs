@@ -0,0 +1,83 @@
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// The largest request head accepted, in bytes.
+const MAX_HEADER_SIZE: usize = 16 * 1024;
+
+/// The largest request body accepted, in bytes.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Serializes `value` as a JSON response body.
+pub fn json(value: &impl serde::Serialize) -> (&'static str, String) {
+    match serde_json::to_string(value) {
+        Ok(body) => ("200 OK", body),
+        Err(_) => ("500 Internal Server Error", String::new()),
+    }
+}
+
+/// Reads an HTTP/1.1 request and returns its method, path and body. Requests
+/// with an oversized head or body are answered with an error status and return None.
+pub async fn read_request(stream: &mut TcpStream) -> Option<(String, String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+        if buf.len() > MAX_HEADER_SIZE {
+            respond(stream, "431 Request Header Fields Too Large", "").await;
+            return None;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut request_line = head.lines().next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_SIZE {
+        respond(stream, "413 Payload Too Large", "").await;
+        return None;
+    }
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Some((method, path, buf[header_end..].to_vec()))
+}
+
+/// Writes a JSON response and closes the connection.
+pub async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    respond_with_type(stream, status, "application/json", body).await;
+}
+
+/// Writes a response of `content_type` and closes the connection.
+pub async fn respond_with_type(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
@@ -1,6 +1,6 @@
 use std::io::Read;
 
-use chrono::{Local, TimeZone};
+use chrono::{Local, NaiveDate, TimeZone};
 use ore_api::consts::TOKEN_DECIMALS;
 
 pub fn amount_u64_to_string(amount: u64) -> String {
@@ -21,6 +21,56 @@ pub fn format_duration(seconds: u32) -> String {
     format!("{:02}:{:02}", minutes, remaining_seconds)
 }
 
+/// Parses an interval such as `90`, `90s`, `30m`, `6h` or `1d`. Bare numbers are seconds.
+pub fn parse_interval(s: &str) -> Result<std::time::Duration, String> {
+    let s = s.trim();
+    let (value, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let value = value
+        .parse::<u64>()
+        .map_err(|_| format!("expected an interval such as 30m or 6h, got \"{}\"", s))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "unknown interval unit \"{}\", expected s, m, h or d",
+                unit
+            ))
+        }
+    };
+    if value == 0 {
+        return Err("interval must be greater than zero".to_string());
+    }
+    Ok(std::time::Duration::from_secs(value * seconds))
+}
+
+/// Parses a date such as `2024-06-01` as the first second of that day in local time.
+pub fn parse_start_of_day(s: &str) -> Result<i64, String> {
+    parse_date(s, 0, 0, 0)
+}
+
+/// Parses a date such as `2024-06-01` as the last second of that day in local time.
+pub fn parse_end_of_day(s: &str) -> Result<i64, String> {
+    parse_date(s, 23, 59, 59)
+}
+
+fn parse_date(s: &str, hour: u32, min: u32, sec: u32) -> Result<i64, String> {
+    let time = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(hour, min, sec))
+        .ok_or_else(|| format!("expected a date such as 2024-06-01, got \"{}\"", s))?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.timestamp())
+        .ok_or_else(|| format!("{} does not exist in the local time zone", s))
+}
+
 pub fn format_timestamp(timestamp: i64) -> String {
     let dt = Local.timestamp_opt(timestamp, 0).unwrap();
     dt.format("%Y-%m-%d %H:%M:%S").to_string()
@@ -37,4 +87,4 @@ pub fn ask_confirm(question: &str) -> bool {
             _ => println!("y/n only please."),
         }
     }
-}
\ No newline at end of file
+}
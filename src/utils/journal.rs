@@ -0,0 +1,183 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::worker::StopReason;
+
+/// The kind of mining round recorded in the journal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundKind {
+    Solo,
+    Pool,
+}
+
+/// The outcome of a mining round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundStatus {
+    Confirmed,
+    Failed,
+}
+
+/// A solution contributed to a pool during a round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Contribution {
+    pub difficulty: u32,
+    pub attempts: u32,
+    pub accepted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A single mining round, as appended to the on-disk journal.
+/// All token amounts are in base units (grains and lamports).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub kind: RoundKind,
+    pub status: RoundStatus,
+    pub timestamp: i64,
+    pub signature: Option<String>,
+    pub slot: Option<u64>,
+    pub difficulty: u64,
+    pub base_reward: u64,
+    pub boost_reward: u64,
+    pub total_reward: u64,
+    pub timing: i64,
+    pub fee: u64,
+    pub tip: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cutoff: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_difficulty: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<StopReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member_difficulty: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member_reward: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributions: Vec<Contribution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl JournalEntry {
+    /// Starts a new round record. The round counts as failed until it is confirmed.
+    pub fn new(kind: RoundKind) -> Self {
+        Self {
+            kind,
+            status: RoundStatus::Failed,
+            timestamp: chrono::Utc::now().timestamp(),
+            signature: None,
+            slot: None,
+            difficulty: 0,
+            base_reward: 0,
+            boost_reward: 0,
+            total_reward: 0,
+            timing: 0,
+            fee: 0,
+            tip: 0,
+            challenge: None,
+            cutoff: None,
+            buffer: None,
+            best_difficulty: None,
+            stop_reason: None,
+            pool_url: None,
+            member_difficulty: None,
+            member_reward: None,
+            contributions: vec![],
+            error: None,
+        }
+    }
+
+    pub fn failed(mut self, error: String) -> Self {
+        self.status = RoundStatus::Failed;
+        self.timestamp = chrono::Utc::now().timestamp();
+        self.error = Some(error);
+        self
+    }
+
+    /// The reward credited to this miner. For pool rounds this is the member's share.
+    pub fn my_reward(&self) -> u64 {
+        self.member_reward.unwrap_or(self.total_reward)
+    }
+
+    /// Contributions the pool accepted, formatted as a rate for display.
+    pub fn acceptance_rate(&self) -> String {
+        let submitted = self.contributions.len();
+        if submitted == 0 {
+            return "–".to_string();
+        }
+        let accepted = self.contributions.iter().filter(|c| c.accepted).count();
+        format!(
+            "{:.0}% ({}/{})",
+            accepted as f64 * 100.0 / submitted as f64,
+            accepted,
+            submitted
+        )
+    }
+
+    /// The difficulty contributed by this miner. For pool rounds this is the member's score.
+    pub fn my_difficulty(&self) -> u64 {
+        self.member_difficulty.unwrap_or(self.difficulty)
+    }
+}
+
+/// An append-only JSONL log of mining rounds.
+#[derive(Clone, Debug)]
+pub struct Journal {
+    pub path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(entry)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Reads every entry in the journal, skipping lines that fail to parse.
+    pub fn read(&self) -> std::io::Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let file = fs::File::open(&self.path)?;
+        let entries = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<JournalEntry>(&line).ok())
+            .collect();
+        Ok(entries)
+    }
+}
+
+/// The default journal location, next to the Solana CLI config directory.
+pub fn default_journal_path() -> PathBuf {
+    let config_dir = solana_cli_config::CONFIG_FILE
+        .as_ref()
+        .and_then(|config_file| Path::new(config_file).parent()?.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("ore").join("journal.jsonl")
+}
@@ -0,0 +1,96 @@
+use indicatif::ProgressBar;
+use ore_pool_types::BalanceUpdate;
+use solana_rpc_client::spinner;
+use solana_sdk::signature::Signature;
+
+use super::JournalEntry;
+
+/// How the miner reports progress and mining rounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Redraw a live table and spinners in the terminal.
+    Table,
+    /// Print one logfmt line per round.
+    Text,
+    /// Print one JSON object per round.
+    Json,
+}
+
+impl LogFormat {
+    pub fn is_headless(&self) -> bool {
+        !matches!(self, LogFormat::Table)
+    }
+
+    /// Returns a terminal spinner, or a hidden progress bar in headless mode.
+    pub fn progress_bar(&self) -> ProgressBar {
+        if self.is_headless() {
+            ProgressBar::hidden()
+        } else {
+            spinner::new_progress_bar()
+        }
+    }
+
+    /// Writes a single round record to stdout. No-op in table mode.
+    pub fn log_round(&self, entry: &JournalEntry) {
+        match self {
+            LogFormat::Table => {}
+            LogFormat::Json => {
+                if let Ok(line) = serde_json::to_string(entry) {
+                    println!("{}", line);
+                }
+            }
+            LogFormat::Text => println!("{}", logfmt(serde_json::to_value(entry))),
+        }
+    }
+
+    /// Writes an automatic claim to stdout. No-op in table mode.
+    pub fn log_claim(&self, signature: &Signature, amount: u64, to: Option<&str>) {
+        let record = serde_json::json!({
+            "event": "claim",
+            "timestamp": chrono::Utc::now().timestamp(),
+            "signature": signature.to_string(),
+            "amount": amount,
+            "to": to,
+        });
+        match self {
+            LogFormat::Table => {}
+            LogFormat::Json => println!("{}", record),
+            LogFormat::Text => println!("{}", logfmt(Ok(record))),
+        }
+    }
+
+    /// Writes a pool balance commit to stdout. No-op in table mode.
+    pub fn log_commit(&self, pool_url: &str, pending: u64, update: &BalanceUpdate) {
+        let record = serde_json::json!({
+            "event": "commit",
+            "timestamp": chrono::Utc::now().timestamp(),
+            "pool_url": pool_url,
+            "pending": pending,
+            "balance": update.balance,
+            "signature": update.signature.to_string(),
+        });
+        match self {
+            LogFormat::Table => {}
+            LogFormat::Json => println!("{}", record),
+            LogFormat::Text => println!("{}", logfmt(Ok(record))),
+        }
+    }
+}
+
+fn logfmt(value: serde_json::Result<serde_json::Value>) -> String {
+    let Ok(serde_json::Value::Object(fields)) = value else {
+        return String::new();
+    };
+    fields
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| match value {
+            serde_json::Value::String(s) if s.contains(' ') || s.contains('"') => {
+                format!("{}={:?}", key, s)
+            }
+            serde_json::Value::String(s) => format!("{}={}", key, s),
+            value => format!("{}={}", key, value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,305 @@
+use std::{
+    fmt::Write as _,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use colored::*;
+use core_affinity::CoreId;
+use solana_program::native_token::lamports_to_sol;
+use tokio::net::TcpListener;
+
+use super::{
+    amount_u64_to_f64, read_request, respond_with_type, JournalEntry, RoundKind, RoundStatus,
+};
+
+/// Counters and gauges collected from the mining loop.
+/// Token amounts are tracked in base units and converted when rendered.
+#[derive(Default)]
+pub struct Metrics {
+    pub hashes: AtomicU64,
+    pub hash_micros: AtomicU64,
+    pub core_hashrates: RwLock<Vec<(Option<usize>, u64)>>,
+    pub best_difficulty: AtomicU64,
+    pub difficulty_total: AtomicU64,
+    pub difficulty_count: AtomicU64,
+    pub rounds_submitted: AtomicU64,
+    pub rounds_confirmed: AtomicU64,
+    pub rounds_failed: AtomicU64,
+    pub base_reward: AtomicU64,
+    pub boost_reward: AtomicU64,
+    pub pool_reward: AtomicU64,
+    pub fees: AtomicU64,
+    pub tips: AtomicU64,
+    pub rpc_errors: AtomicU64,
+    pub contributions_submitted: AtomicU64,
+    pub contributions_accepted: AtomicU64,
+    pub send_micros: AtomicU64,
+    pub send_count: AtomicU64,
+}
+
+impl Metrics {
+    /// Records the hashes each worker computed in a round. `cores` holds the core each
+    /// worker is pinned to, in the same order.
+    pub fn record_hashes(&self, cores: &[Option<CoreId>], core_hashes: &[u64], elapsed: Duration) {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        self.hashes
+            .fetch_add(core_hashes.iter().sum(), Ordering::Relaxed);
+        self.hash_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        *self.core_hashrates.write().unwrap() = core_hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hashes)| {
+                let core = cores.get(i).copied().flatten().map(|core| core.id);
+                (core, (*hashes as f64 / secs) as u64)
+            })
+            .collect();
+    }
+
+    pub fn record_send(&self, elapsed: Duration) {
+        self.send_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.send_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_rpc_error(&self) {
+        self.rpc_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_contribution(&self, accepted: bool) {
+        self.contributions_submitted.fetch_add(1, Ordering::Relaxed);
+        if accepted {
+            self.contributions_accepted.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_round(&self, entry: &JournalEntry) {
+        if let Some(difficulty) = entry.best_difficulty {
+            self.best_difficulty.store(difficulty, Ordering::Relaxed);
+            self.difficulty_total
+                .fetch_add(difficulty, Ordering::Relaxed);
+            self.difficulty_count.fetch_add(1, Ordering::Relaxed);
+        }
+        self.fees.fetch_add(entry.fee, Ordering::Relaxed);
+        match entry.status {
+            RoundStatus::Failed => {
+                self.rounds_failed.fetch_add(1, Ordering::Relaxed);
+            }
+            RoundStatus::Confirmed => {
+                self.rounds_confirmed.fetch_add(1, Ordering::Relaxed);
+                self.tips.fetch_add(entry.tip, Ordering::Relaxed);
+                match entry.kind {
+                    RoundKind::Solo => {
+                        self.base_reward
+                            .fetch_add(entry.base_reward, Ordering::Relaxed);
+                        self.boost_reward
+                            .fetch_add(entry.boost_reward, Ordering::Relaxed);
+                    }
+                    RoundKind::Pool => {
+                        self.pool_reward
+                            .fetch_add(entry.my_reward(), Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Average best difficulty across all recorded rounds.
+    pub fn average_difficulty(&self) -> f64 {
+        let count = self.difficulty_count.load(Ordering::Relaxed);
+        if count == 0 {
+            return 0f64;
+        }
+        self.difficulty_total.load(Ordering::Relaxed) as f64 / count as f64
+    }
+
+    /// Hashes per second across all cores, averaged over the time spent hashing.
+    pub fn effective_hashrate(&self) -> f64 {
+        let micros = self.hash_micros.load(Ordering::Relaxed);
+        if micros == 0 {
+            return 0f64;
+        }
+        self.hashes.load(Ordering::Relaxed) as f64 / (micros as f64 / 1_000_000f64)
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        metric(&mut out, "ore_hashes_total", "counter", "Hashes computed.");
+        sample(&mut out, "ore_hashes_total", "", load(&self.hashes));
+        metric(
+            &mut out,
+            "ore_hashrate",
+            "gauge",
+            "Hashes per second per worker during the last round, labeled with its pinned core.",
+        );
+        for (worker, (core, hashrate)) in self.core_hashrates.read().unwrap().iter().enumerate() {
+            let core = core.map_or("none".to_string(), |core| core.to_string());
+            sample(
+                &mut out,
+                "ore_hashrate",
+                &format!("worker=\"{}\",core=\"{}\"", worker, core),
+                hashrate,
+            );
+        }
+        metric(
+            &mut out,
+            "ore_best_difficulty",
+            "gauge",
+            "Best difficulty found in the last round.",
+        );
+        sample(
+            &mut out,
+            "ore_best_difficulty",
+            "",
+            load(&self.best_difficulty),
+        );
+        metric(&mut out, "ore_rounds_total", "counter", "Mining rounds.");
+        sample(
+            &mut out,
+            "ore_rounds_total",
+            "status=\"submitted\"",
+            load(&self.rounds_submitted),
+        );
+        sample(
+            &mut out,
+            "ore_rounds_total",
+            "status=\"confirmed\"",
+            load(&self.rounds_confirmed),
+        );
+        sample(
+            &mut out,
+            "ore_rounds_total",
+            "status=\"failed\"",
+            load(&self.rounds_failed),
+        );
+        metric(&mut out, "ore_earned_total", "counter", "ORE earned.");
+        sample(
+            &mut out,
+            "ore_earned_total",
+            "source=\"base\"",
+            amount_u64_to_f64(load(&self.base_reward)),
+        );
+        sample(
+            &mut out,
+            "ore_earned_total",
+            "source=\"boost\"",
+            amount_u64_to_f64(load(&self.boost_reward)),
+        );
+        sample(
+            &mut out,
+            "ore_earned_total",
+            "source=\"pool\"",
+            amount_u64_to_f64(load(&self.pool_reward)),
+        );
+        metric(
+            &mut out,
+            "ore_spent_sol_total",
+            "counter",
+            "SOL spent on transaction fees and Jito tips.",
+        );
+        sample(
+            &mut out,
+            "ore_spent_sol_total",
+            "kind=\"fee\"",
+            lamports_to_sol(load(&self.fees)),
+        );
+        sample(
+            &mut out,
+            "ore_spent_sol_total",
+            "kind=\"tip\"",
+            lamports_to_sol(load(&self.tips)),
+        );
+        metric(
+            &mut out,
+            "ore_pool_contributions_total",
+            "counter",
+            "Solutions contributed to pools.",
+        );
+        sample(
+            &mut out,
+            "ore_pool_contributions_total",
+            "status=\"submitted\"",
+            load(&self.contributions_submitted),
+        );
+        sample(
+            &mut out,
+            "ore_pool_contributions_total",
+            "status=\"accepted\"",
+            load(&self.contributions_accepted),
+        );
+        metric(&mut out, "ore_rpc_errors_total", "counter", "RPC errors.");
+        sample(&mut out, "ore_rpc_errors_total", "", load(&self.rpc_errors));
+        metric(
+            &mut out,
+            "ore_send_and_confirm_seconds",
+            "summary",
+            "Time spent sending and confirming mine transactions.",
+        );
+        sample(
+            &mut out,
+            "ore_send_and_confirm_seconds_sum",
+            "",
+            load(&self.send_micros) as f64 / 1_000_000f64,
+        );
+        sample(
+            &mut out,
+            "ore_send_and_confirm_seconds_count",
+            "",
+            load(&self.send_count),
+        );
+        out
+    }
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+/// Serves the metrics on `GET /metrics` until the process exits.
+pub async fn serve_metrics(addr: SocketAddr, metrics: Arc<Metrics>) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            println!(
+                "{} Failed to bind metrics server to {}: {}",
+                "ERROR".bold().red(),
+                addr,
+                err
+            );
+            return;
+        }
+    };
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let Some((method, path, _)) = read_request(&mut stream).await else {
+                return;
+            };
+            let (status, body) = if method == "GET" && path == "/metrics" {
+                ("200 OK", metrics.render())
+            } else {
+                ("404 Not Found", "Not found\n".to_string())
+            };
+            respond_with_type(&mut stream, status, "text/plain; version=0.0.4", &body).await;
+        });
+    }
+}
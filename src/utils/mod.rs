@@ -1,8 +1,32 @@
+mod buffer;
+mod cores;
+mod device;
+mod http;
 mod io;
+mod journal;
+mod log;
+mod metrics;
+mod notify;
+mod probe;
+mod rewards;
 mod rpc;
+mod shutdown;
 mod table;
+mod verify;
 
+pub use buffer::*;
+pub use cores::*;
+pub use device::*;
+pub use http::*;
 pub use io::*;
+pub use journal::*;
+pub use log::*;
+pub use metrics::*;
+pub use notify::*;
+pub use probe::*;
+pub use rewards::*;
 pub use rpc::*;
+pub use shutdown::*;
 pub use table::*;
+pub use verify::*;
 
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use colored::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Seconds to wait for a webhook or hook command before giving up.
+const NOTIFY_TIMEOUT: u64 = 10;
+
+/// Miner events that can be routed to external alerting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    RoundConfirmed,
+    RoundFailed,
+    MaxRetries,
+    BalanceLow,
+    PoolUnreachable,
+    AutoClaim,
+    ChallengeMismatch,
+    PoolCommit,
+}
+
+/// Sends miner events to a webhook and/or a shell command.
+#[derive(Default)]
+pub struct Notifier {
+    webhook_url: Option<String>,
+    exec: Option<String>,
+    http_client: reqwest::Client,
+}
+
+impl Notifier {
+    pub fn new(webhook_url: Option<String>, exec: Option<String>) -> Self {
+        Self {
+            webhook_url,
+            exec,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Fires `event` in the background. `details` should be a JSON object.
+    ///
+    /// The webhook receives a JSON payload with `event`, `timestamp` and `details`. The
+    /// command runs through `sh -c` with the payload in `ORE_EVENT_JSON`, the event name in
+    /// `ORE_EVENT`, the time in `ORE_TIMESTAMP` and each scalar detail in `ORE_<KEY>`.
+    pub fn notify(&self, event: NotifyEvent, details: Value) {
+        if self.webhook_url.is_none() && self.exec.is_none() {
+            return;
+        }
+        let payload = json!({
+            "event": event,
+            "timestamp": chrono::Utc::now().timestamp(),
+            "details": details,
+        });
+
+        // Post to webhook
+        if let Some(url) = self.webhook_url.clone() {
+            let request = self
+                .http_client
+                .post(url)
+                .timeout(Duration::from_secs(NOTIFY_TIMEOUT))
+                .json(&payload);
+            tokio::spawn(async move {
+                let result = request.send().await.and_then(|res| res.error_for_status());
+                if let Err(err) = result {
+                    println!(
+                        "{} Failed to post notification: {}",
+                        "WARNING".bold().yellow(),
+                        err
+                    );
+                }
+            });
+        }
+
+        // Run hook command
+        if let Some(exec) = self.exec.clone() {
+            let mut command = tokio::process::Command::new("sh");
+            command
+                .arg("-c")
+                .arg(exec)
+                .env("ORE_EVENT_JSON", payload.to_string())
+                .kill_on_drop(true);
+            let fields = payload.as_object().into_iter().flatten();
+            let details = payload["details"].as_object().into_iter().flatten();
+            for (key, value) in fields.chain(details) {
+                if let Some(value) = scalar(value) {
+                    command.env(format!("ORE_{}", key.to_uppercase()), value);
+                }
+            }
+            tokio::spawn(async move {
+                let result =
+                    tokio::time::timeout(Duration::from_secs(NOTIFY_TIMEOUT), command.status())
+                        .await;
+                match result {
+                    Ok(Ok(status)) if status.success() => {}
+                    Ok(Ok(status)) => println!(
+                        "{} Notification command exited with {}",
+                        "WARNING".bold().yellow(),
+                        status
+                    ),
+                    Ok(Err(err)) => println!(
+                        "{} Failed to run notification command: {}",
+                        "WARNING".bold().yellow(),
+                        err
+                    ),
+                    Err(_) => println!(
+                        "{} Notification command timed out",
+                        "WARNING".bold().yellow()
+                    ),
+                }
+            });
+        }
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+use ore_pool_types::{MemberChallenge, PoolAddress};
+use solana_program::pubkey::Pubkey;
+
+/// How long to wait on each pool endpoint before marking the pool unreachable.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The score to rank pools by when listing them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum PoolSort {
+    /// Fastest pool server first. Unprobed pools sort last.
+    Latency,
+    /// Most lifetime rewards per lifetime hash first.
+    RewardsPerHash,
+    /// Most members first.
+    Members,
+    /// Largest proof balance first.
+    Balance,
+}
+
+/// What a pool server answered when contacted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolStatus {
+    /// The server reported the expected address and served a challenge.
+    Ok,
+    /// The pool account has no url.
+    NoUrl,
+    /// The server could not be reached or did not answer in time.
+    Unreachable,
+    /// The server reported a different pool address than the account it is listed under.
+    AddressMismatch(Pubkey),
+    /// The server reported the expected address but did not serve a challenge.
+    NoChallenge,
+}
+
+/// The result of contacting a pool server.
+#[derive(Clone, Debug)]
+pub struct PoolProbe {
+    /// Round trip of the `/address` request.
+    pub address_latency: Option<Duration>,
+    /// Round trip of the `/challenge` request.
+    pub challenge_latency: Option<Duration>,
+    pub status: PoolStatus,
+}
+
+impl PoolProbe {
+    /// The slower of the two round trips, which bounds how quickly a member sees a new round.
+    pub fn latency(&self) -> Option<Duration> {
+        match (self.address_latency, self.challenge_latency) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Contacts the pool server at `url`, checking that it reports `pool_address` and serves a
+/// challenge for `authority`. Never fails; problems are recorded in the returned status.
+pub async fn probe_pool(
+    http_client: &reqwest::Client,
+    url: &str,
+    pool_address: Pubkey,
+    authority: Pubkey,
+) -> PoolProbe {
+    let url = url.trim_end_matches('/');
+    let mut probe = PoolProbe {
+        address_latency: None,
+        challenge_latency: None,
+        status: PoolStatus::NoUrl,
+    };
+    if url.is_empty() {
+        return probe;
+    }
+
+    // Check the reported address
+    let start = Instant::now();
+    let address = match get_json::<PoolAddress>(http_client, format!("{}/address", url)).await {
+        Some(address) => address.address,
+        None => {
+            probe.status = PoolStatus::Unreachable;
+            return probe;
+        }
+    };
+    probe.address_latency = Some(start.elapsed());
+    if address != pool_address {
+        probe.status = PoolStatus::AddressMismatch(address);
+        return probe;
+    }
+
+    // Check the served challenge
+    let start = Instant::now();
+    let challenge =
+        get_json::<MemberChallenge>(http_client, format!("{}/challenge/{}", url, authority)).await;
+    probe.status = match challenge {
+        Some(_) => {
+            probe.challenge_latency = Some(start.elapsed());
+            PoolStatus::Ok
+        }
+        None => PoolStatus::NoChallenge,
+    };
+    probe
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    http_client: &reqwest::Client,
+    url: String,
+) -> Option<T> {
+    let resp = http_client
+        .get(url)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    resp.json::<T>().await.ok()
+}
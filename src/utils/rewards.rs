@@ -0,0 +1,36 @@
+use ore_api::{consts::ONE_ORE, state::Config};
+
+/// Highest difficulty offset above the minimum that the reward table covers.
+const MAX_DIFFICULTY_OFFSET: u32 = 32;
+
+/// Returns the reward paid at each difficulty, starting at the minimum difficulty and
+/// ending at the first difficulty whose reward reaches 1 ORE.
+pub fn reward_rates(config: &Config) -> Vec<(u64, u64)> {
+    let mut rates = vec![];
+    for i in 0..MAX_DIFFICULTY_OFFSET {
+        let reward_rate = config
+            .base_reward_rate
+            .saturating_mul(2u64.saturating_pow(i));
+        let reward = reward_rate.min(ONE_ORE);
+        rates.push((config.min_difficulty + i as u64, reward));
+        if reward >= ONE_ORE {
+            break;
+        }
+    }
+    rates
+}
+
+/// Returns the expected reward, in grains, of a round in which `hashes` hashes are tried.
+/// A hash meets difficulty `d` with probability 2^-d, so the best of `hashes` hashes meets
+/// it with probability 1 - (1 - 2^-d)^hashes.
+pub fn expected_reward(rates: &[(u64, u64)], hashes: f64) -> f64 {
+    let mut expected = 0f64;
+    let mut previous = 0u64;
+    for (difficulty, reward) in rates {
+        let p = 2f64.powi(-(*difficulty as i32));
+        let p_best = -(hashes * (-p).ln_1p()).exp_m1();
+        expected += p_best * reward.saturating_sub(previous) as f64;
+        previous = *reward;
+    }
+    expected
+}
@@ -25,9 +25,9 @@ pub const BLOCKHASH_QUERY_RETRIES: usize = 5;
 pub const BLOCKHASH_QUERY_DELAY: u64 = 500;
 
 pub enum ComputeBudget {
-    #[allow(dead_code)]
-    Dynamic,
-    Fixed(u32),
+    /// Size the compute unit limit to a simulation of the transaction, falling back to the
+    /// given limit if the simulation fails.
+    Dynamic(u32),
 }
 
 pub async fn get_program_accounts<T>(
@@ -100,11 +100,14 @@ pub async fn get_mint(client: &RpcClient, address: Pubkey) -> Result<Mint, anyho
 }
 
 pub async fn get_config(client: &RpcClient) -> Config {
-    let data = client
-        .get_account_data(&CONFIG_ADDRESS)
+    try_get_config(client)
         .await
-        .expect("Failed to get config account");
-    *Config::try_from_bytes(&data).expect("Failed to parse config account")
+        .expect("Failed to get config account")
+}
+
+pub async fn try_get_config(client: &RpcClient) -> Result<Config, anyhow::Error> {
+    let data = client.get_account_data(&CONFIG_ADDRESS).await?;
+    Ok(*Config::try_from_bytes(&data)?)
 }
 
 pub async fn get_boost_config(client: &RpcClient) -> ore_boost_api::state::Config {
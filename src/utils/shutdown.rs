@@ -0,0 +1,77 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Arc,
+    time::Duration,
+};
+
+use colored::*;
+use tokio::sync::Notify;
+
+/// Tracks SIGINT/SIGTERM so long-running commands can wind down between rounds.
+#[derive(Default)]
+pub struct Shutdown {
+    requested: AtomicBool,
+    notify: Notify,
+}
+
+impl Shutdown {
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+
+    /// Resolves once a shutdown has been requested.
+    pub async fn wait(&self) {
+        let notified = self.notify.notified();
+        if self.is_requested() {
+            return;
+        }
+        notified.await;
+    }
+
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    /// Listens for termination signals. The first signal requests a graceful shutdown
+    /// and gives in-flight work `timeout` to finish, calling `on_timeout` before exiting
+    /// if it does not. A second signal exits immediately.
+    pub fn listen(self: &Arc<Self>, timeout: Duration, on_timeout: impl FnOnce() + Send + 'static) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            shutdown.request();
+            eprintln!(
+                "\n{} Shutting down after the current round. Press Ctrl-C again to force exit.",
+                "INFO".bold().cyan()
+            );
+            tokio::spawn(async move {
+                tokio::time::sleep(timeout).await;
+                eprintln!(
+                    "{} Shutdown timed out after {}s",
+                    "ERROR".bold().red(),
+                    timeout.as_secs()
+                );
+                on_timeout();
+                std::process::exit(1);
+            });
+            wait_for_signal().await;
+            std::process::exit(130);
+        });
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -1,6 +1,7 @@
 use colored::Colorize;
 use solana_sdk::signature::Signature;
 use tabled::{Tabled, settings::{object::Rows, style::{BorderColor, LineText}, Color, Border, Highlight, Padding}, Table};
+use super::{amount_u64_to_f64, format_timestamp};
 
 #[derive(Tabled)]
 pub struct TableData {
@@ -34,6 +35,8 @@ pub struct SoloMiningData {
     pub timestamp: String,
     #[tabled(rename = "Timing")]
     pub timing: String,
+    #[tabled(rename = "Buffer")]
+    pub buffer: String,
     #[tabled(rename = "Score")]
     pub difficulty: String,
     #[tabled(rename = "Base Reward")]
@@ -57,6 +60,7 @@ impl SoloMiningData {
             boost_reward: "–".to_string(),
             total_reward: "–".to_string(),
             timing: "–".to_string(),
+            buffer: "–".to_string(),
             status: "Fetching".to_string(),
         }
     }
@@ -71,9 +75,25 @@ impl SoloMiningData {
             boost_reward: "–".to_string(),
             total_reward: "–".to_string(),
             timing: "–".to_string(),
+            buffer: "–".to_string(),
             status: "Failed".bold().red().to_string(),
         }
     }
+
+    pub fn claimed(signature: String, amount: u64) -> Self {
+        Self {
+            signature,
+            block: "–".to_string(),
+            timestamp: format_timestamp(chrono::Utc::now().timestamp()),
+            difficulty: "–".to_string(),
+            base_reward: "–".to_string(),
+            boost_reward: "–".to_string(),
+            total_reward: format!("-{:#.11}", amount_u64_to_f64(amount)),
+            timing: "–".to_string(),
+            buffer: "–".to_string(),
+            status: "Claimed".bold().cyan().to_string(),
+        }
+    }
 }
 
 
@@ -87,6 +107,8 @@ pub struct PoolMiningData {
     pub timestamp: String,
     #[tabled(rename = "Timing")]
     pub timing: String,
+    #[tabled(rename = "Buffer")]
+    pub buffer: String,
     #[tabled(rename = "Score")]
     pub difficulty: String,
     #[tabled(rename = "Pool Base Reward")]
@@ -99,4 +121,28 @@ pub struct PoolMiningData {
     pub my_difficulty: String,
     #[tabled(rename = "My Reward")]
     pub my_reward: String,
+    #[tabled(rename = "Accepted")]
+    pub accepted: String,
+    #[tabled(rename = "Pool")]
+    pub pool: String,
+}
+
+impl PoolMiningData {
+    pub fn failed(message: String, accepted: String, pool: String) -> Self {
+        Self {
+            signature: message,
+            block: "".to_string(),
+            timestamp: "".to_string(),
+            timing: "".to_string(),
+            buffer: "".to_string(),
+            difficulty: "".to_string(),
+            base_reward: "".to_string(),
+            boost_reward: "".to_string(),
+            total_reward: "".to_string(),
+            my_difficulty: "".to_string(),
+            my_reward: "".to_string(),
+            accepted,
+            pool,
+        }
+    }
 }
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use ore_api::state::proof_pda;
+use ore_pool_types::MemberChallenge;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+
+use super::{get_proof, try_get_config};
+
+/// Times to re-read the pool's proof when the challenge is newer than the RPC's view of it.
+const PROOF_LAG_RETRIES: usize = 4;
+
+/// How long to wait before re-reading the pool's proof.
+const PROOF_LAG_DELAY: Duration = Duration::from_millis(250);
+
+/// What to do when a pool serves a challenge that does not match the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ChallengeCheck {
+    /// Warn and keep mining the challenge.
+    Warn,
+    /// Stop mining with an error.
+    Stop,
+}
+
+/// Checks that a pool's challenge matches its proof and the program config on-chain.
+/// Returns a description of the mismatch if it does not.
+pub async fn verify_pool_challenge(
+    client: &RpcClient,
+    pool_address: Pubkey,
+    challenge: &MemberChallenge,
+) -> Result<(), String> {
+    let (proof, config) = tokio::join!(
+        verify_proof(client, pool_address, challenge),
+        try_get_config(client)
+    );
+    proof?;
+
+    // Compare against the program config
+    let config = config.map_err(|err| format!("failed to fetch config: {}", err))?;
+    if challenge.challenge.min_difficulty != config.min_difficulty {
+        return Err(format!(
+            "min difficulty {} does not match config ({})",
+            challenge.challenge.min_difficulty, config.min_difficulty
+        ));
+    }
+    Ok(())
+}
+
+/// Compares a challenge against the pool's proof, allowing the RPC to catch up with a new
+/// round.
+async fn verify_proof(
+    client: &RpcClient,
+    pool_address: Pubkey,
+    challenge: &MemberChallenge,
+) -> Result<(), String> {
+    let proof_address = proof_pda(pool_address).0;
+    let mut attempts = 0;
+    let proof = loop {
+        let proof = get_proof(client, proof_address)
+            .await
+            .map_err(|err| format!("failed to fetch pool proof {}: {}", proof_address, err))?;
+        attempts += 1;
+        if challenge.challenge.lash_hash_at <= proof.last_hash_at || attempts > PROOF_LAG_RETRIES {
+            break proof;
+        }
+        tokio::time::sleep(PROOF_LAG_DELAY).await;
+    };
+    if challenge.challenge.lash_hash_at < proof.last_hash_at {
+        return Err(format!(
+            "stale challenge from {} (proof last hashed at {})",
+            challenge.challenge.lash_hash_at, proof.last_hash_at
+        ));
+    }
+    if challenge.challenge.lash_hash_at != proof.last_hash_at {
+        return Err(format!(
+            "last hash at {} does not match proof ({})",
+            challenge.challenge.lash_hash_at, proof.last_hash_at
+        ));
+    }
+    if challenge.challenge.challenge != proof.challenge {
+        return Err("challenge does not match proof".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,3 @@
+mod worker_pool;
+
+pub use worker_pool::*;
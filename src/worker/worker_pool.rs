@@ -0,0 +1,337 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering},
+        mpsc, Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use colored::*;
+use core_affinity::CoreId;
+use drillx::{equix, Hash, Solution};
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::utils::format_duration;
+
+/// A unit of hashing work for a single round.
+pub struct Job {
+    /// The challenge to hash against.
+    pub challenge: [u8; 32],
+
+    /// The first nonce for each worker. Must have one entry per worker.
+    pub nonces: Vec<u64>,
+
+    /// Time after which workers stop as soon as `min_difficulty` is met.
+    pub deadline: Instant,
+
+    /// The minimum difficulty accepted by the program.
+    pub min_difficulty: u32,
+
+    /// Optional channel to stream global improvements to, e.g. for pool submissions.
+    pub improvements: Option<UnboundedSender<Solution>>,
+
+    /// Conditions for stopping before the deadline.
+    pub policy: StopPolicy,
+}
+
+/// Optional conditions for ending a round before the deadline.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StopPolicy {
+    /// Stop as soon as any worker reaches this difficulty (and the minimum difficulty).
+    pub target_difficulty: Option<u32>,
+
+    /// Stop after this long, whatever the best difficulty is.
+    pub max_duration: Option<Duration>,
+}
+
+/// Why workers stopped hashing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StopReason {
+    /// The deadline passed and the minimum difficulty was met.
+    Cutoff,
+    /// The target difficulty was reached.
+    Target,
+    /// The maximum round duration elapsed.
+    Timeout,
+    /// The round was cancelled.
+    Cancelled,
+}
+
+impl StopReason {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(StopReason::Cutoff),
+            2 => Some(StopReason::Target),
+            3 => Some(StopReason::Timeout),
+            4 => Some(StopReason::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// The outcome of a round across all workers.
+pub struct JobResult {
+    pub solution: Solution,
+    /// Hashes computed by each worker.
+    pub worker_hashes: Vec<u64>,
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+/// State shared by all workers during a round.
+struct Round {
+    best_difficulty: AtomicU32,
+    cancelled: AtomicBool,
+    stop_reason: AtomicU8,
+}
+
+impl Round {
+    /// Records why the round ended. The first worker to stop decides.
+    fn stop(&self, reason: StopReason) {
+        let _ = self.stop_reason.compare_exchange(
+            0,
+            reason as u8 + 1,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+struct WorkerJob {
+    challenge: [u8; 32],
+    nonce: u64,
+    deadline: Instant,
+    max_deadline: Option<Instant>,
+    min_difficulty: u32,
+    target_difficulty: Option<u32>,
+    improvements: Option<UnboundedSender<Solution>>,
+    round: Arc<Round>,
+    results: UnboundedSender<WorkerResult>,
+}
+
+struct WorkerResult {
+    nonce: u64,
+    difficulty: u32,
+    hash: Hash,
+    hashes: u64,
+}
+
+/// A long-lived set of hashing threads. Each worker is pinned once and keeps
+/// its solver memory across rounds.
+pub struct WorkerPool {
+    cores: Vec<Option<CoreId>>,
+    senders: Vec<mpsc::Sender<WorkerJob>>,
+    handles: Vec<JoinHandle<()>>,
+    current: std::sync::Mutex<Option<Arc<Round>>>,
+}
+
+impl WorkerPool {
+    /// Spawns one worker per entry. Workers with a core id are pinned to that core.
+    pub fn new(cores: Vec<Option<CoreId>>) -> Self {
+        let mut senders = Vec::with_capacity(cores.len());
+        let mut handles = Vec::with_capacity(cores.len());
+        for core in cores.iter().copied() {
+            let (tx, rx) = mpsc::channel::<WorkerJob>();
+            senders.push(tx);
+            handles.push(std::thread::spawn(move || {
+                // Pin to core
+                if let Some(core) = core {
+                    let _ = core_affinity::set_for_current(core);
+                }
+
+                // Process jobs until the pool is dropped
+                let mut memory = equix::SolverMemory::new();
+                while let Ok(job) = rx.recv() {
+                    let result = hash(&mut memory, &job);
+                    let _ = job.results.send(result);
+                }
+            }));
+        }
+        Self {
+            cores,
+            senders,
+            handles,
+            current: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// The core each worker is pinned to, if any, in worker order.
+    pub fn cores(&self) -> &[Option<CoreId>] {
+        &self.cores
+    }
+
+    /// Stops the current round early. Workers return their best result so far.
+    pub fn cancel(&self) {
+        if let Some(round) = self.current.lock().unwrap().as_ref() {
+            round.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Dispatches a job to all workers and waits for their results.
+    pub async fn run(&self, job: Job, progress_bar: &ProgressBar) -> JobResult {
+        let timer = Instant::now();
+        let round = Arc::new(Round {
+            best_difficulty: AtomicU32::new(0),
+            cancelled: AtomicBool::new(false),
+            stop_reason: AtomicU8::new(0),
+        });
+        *self.current.lock().unwrap() = Some(round.clone());
+
+        // Dispatch job to each worker
+        let (results_tx, mut results_rx) = unbounded_channel();
+        for (sender, nonce) in self.senders.iter().zip(job.nonces.iter()) {
+            let _ = sender.send(WorkerJob {
+                challenge: job.challenge,
+                nonce: *nonce,
+                deadline: job.deadline,
+                max_deadline: job.policy.max_duration.map(|duration| timer + duration),
+                min_difficulty: job.min_difficulty,
+                target_difficulty: job
+                    .policy
+                    .target_difficulty
+                    .map(|target| target.max(job.min_difficulty)),
+                improvements: job.improvements.clone(),
+                round: round.clone(),
+                results: results_tx.clone(),
+            });
+        }
+        drop(results_tx);
+
+        // Collect results while reporting progress
+        progress_bar.set_message("Mining...");
+        let mut results = Vec::with_capacity(self.len());
+        loop {
+            tokio::select! {
+                result = results_rx.recv() => match result {
+                    Some(result) => results.push(result),
+                    None => break,
+                },
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                    let best_difficulty = round.best_difficulty.load(Ordering::Relaxed);
+                    let remaining = job.deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        progress_bar
+                            .set_message(format!("Mining...\n  Best score: {}", best_difficulty));
+                    } else {
+                        progress_bar.set_message(format!(
+                            "Mining...\n  Best score: {}\n  Time remaining: {}",
+                            best_difficulty,
+                            format_duration(remaining.as_secs() as u32),
+                        ));
+                    }
+                }
+            }
+        }
+        *self.current.lock().unwrap() = None;
+
+        // Select the best result
+        let mut best = (0u64, 0u32, Hash::default());
+        let mut worker_hashes = Vec::with_capacity(results.len());
+        for result in results {
+            worker_hashes.push(result.hashes);
+            if result.difficulty > best.1 {
+                best = (result.nonce, result.difficulty, result.hash);
+            }
+        }
+        JobResult {
+            solution: Solution::new(best.2.d, best.0.to_le_bytes()),
+            worker_hashes,
+            elapsed: timer.elapsed(),
+            stop_reason: StopReason::from_u8(round.stop_reason.load(Ordering::Relaxed))
+                .unwrap_or(StopReason::Cutoff),
+        }
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.cancel();
+        self.senders.clear();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn hash(memory: &mut equix::SolverMemory, job: &WorkerJob) -> WorkerResult {
+    let first_nonce = job.nonce;
+    let mut nonce = job.nonce;
+    let mut best_nonce = nonce;
+    let mut best_difficulty = 0;
+    let mut best_hash = Hash::default();
+    loop {
+        // Get hashes
+        let hxs = drillx::hashes_with_memory(memory, &job.challenge, &nonce.to_le_bytes());
+
+        // Look for best difficulty score in all hashes
+        for hx in hxs {
+            let difficulty = hx.difficulty();
+            if difficulty.gt(&best_difficulty) {
+                best_nonce = nonce;
+                best_difficulty = difficulty;
+                best_hash = hx;
+
+                // Update best global difficulty
+                let global_best = job
+                    .round
+                    .best_difficulty
+                    .fetch_max(difficulty, Ordering::Relaxed);
+
+                // Continuously upload best solution to pool
+                if difficulty.gt(&global_best) && difficulty.ge(&job.min_difficulty) {
+                    if let Some(ref ch) = job.improvements {
+                        let solution = Solution::new(best_hash.d, nonce.to_le_bytes());
+                        if let Err(err) = ch.send(solution) {
+                            println!("{} {:?}", "ERROR".bold().red(), err);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Exit if cancelled, if a stop policy is met, or if time has elapsed and min difficulty has been met
+        if nonce % 100 == 0 {
+            let now = Instant::now();
+            let global_best = job.round.best_difficulty.load(Ordering::Relaxed);
+            let reason = if job.round.cancelled.load(Ordering::Relaxed) {
+                Some(StopReason::Cancelled)
+            } else if job
+                .target_difficulty
+                .is_some_and(|target| global_best.ge(&target))
+            {
+                Some(StopReason::Target)
+            } else if job
+                .max_deadline
+                .is_some_and(|max_deadline| now.ge(&max_deadline))
+            {
+                Some(StopReason::Timeout)
+            } else if now.ge(&job.deadline) && global_best.ge(&job.min_difficulty) {
+                Some(StopReason::Cutoff)
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                job.round.stop(reason);
+                break;
+            }
+        }
+
+        // Increment nonce
+        nonce += 1;
+    }
+
+    WorkerResult {
+        nonce: best_nonce,
+        difficulty: best_difficulty,
+        hash: best_hash,
+        hashes: nonce - first_nonce,
+    }
+}